@@ -0,0 +1,381 @@
+use crate::attr::{self, ContainerAttrs, Errors, FieldDefault};
+use crate::ty::{self, Item};
+use quote::format_ident;
+use syn::ext::IdentExt;
+use syn::parse_quote;
+use syn::spanned::Spanned;
+use syn::{
+    Data, DeriveInput, Error, Fields, Generics, Ident, Member, Path, Result, Type, Visibility,
+};
+
+/// The derive input, reduced to one builder per constructible shape.
+pub struct Input<'a> {
+    pub ident: &'a Ident,
+    pub vis: &'a Visibility,
+    pub generics: &'a Generics,
+    pub targets: Vec<Target<'a>>,
+}
+
+/// A struct, or one variant of an enum, together with the builder made for it.
+pub struct Target<'a> {
+    /// `CommandBuilder` for a struct, `MessageRequestBuilder` for a variant.
+    pub builder: Ident,
+    /// The error returned by `build()`, named after the builder.
+    pub error: Ident,
+    /// The associated function on the input type that returns the builder.
+    pub ctor: Ident,
+    /// The method on the input type that copies a value into a builder.
+    pub to_builder: Ident,
+    /// `Some` when the target is an enum variant.
+    pub variant: Option<&'a Ident>,
+    pub attrs: ContainerAttrs,
+    pub fields: Vec<Field<'a>>,
+}
+
+pub struct Field<'a> {
+    /// The name of the setter and of the builder's storage for the field: the
+    /// field's own name, or `_0`, `_1`, ... or `#[builder(name = "...")]` for
+    /// a tuple field.
+    pub ident: Ident,
+    /// How the field is reached on the struct or variant.
+    pub member: Member,
+    pub ty: &'a Type,
+    pub kind: Kind<'a>,
+    pub default: Option<FieldDefault>,
+    /// Whether setters take `impl Into<T>` instead of `T`.
+    pub into: bool,
+    /// `None` when `#[builder(setter(skip))]` leaves the field without one.
+    pub setter: Option<Setter>,
+}
+
+/// The setter taking the field's whole value.
+pub struct Setter {
+    pub name: Ident,
+    pub vis: Visibility,
+    /// Whether an `Option<T>` field is set with a `T`. Always true for other
+    /// fields.
+    pub strip_option: bool,
+    /// Whether a `try_` setter is added next to this one.
+    pub try_setter: bool,
+}
+
+pub enum Kind<'a> {
+    /// Must be set before `build()` succeeds, unless the field has a default.
+    Required,
+    /// Written as `Option<T>`; the setter takes the `T`.
+    Optional(&'a Type),
+    /// `#[builder(each = "...")]` on a `Default + Extend` collection; the
+    /// setter adds one element.
+    Repeated { each: Ident, item: Item<'a> },
+    /// `#[builder(sub_builder)]`; the builder keeps the field's own builder,
+    /// `SandboxBuilder` for a `Sandbox`, and builds it in `build()`.
+    Nested {
+        builder: Box<Type>,
+        error: Box<Path>,
+    },
+}
+
+impl<'a> Input<'a> {
+    pub fn from_syn(node: &'a DeriveInput) -> Result<Self> {
+        let ident = &node.ident;
+        let mut errors = Errors::default();
+        let attrs = attr::container_attrs(&node.attrs, &ContainerAttrs::default()).unwrap_or_else(
+            |error| {
+                errors.push(error);
+                ContainerAttrs::default()
+            },
+        );
+
+        let mut targets = Vec::new();
+        match &node.data {
+            Data::Struct(data) => targets.push(Target::new(
+                format_ident!("{}Builder", ident),
+                format_ident!("builder"),
+                format_ident!("to_builder"),
+                None,
+                attrs,
+                &data.fields,
+            )),
+            Data::Enum(data) => {
+                for variant in &data.variants {
+                    let attrs = match attr::container_attrs(&variant.attrs, &attrs) {
+                        Ok(attrs) => attrs,
+                        Err(error) => {
+                            errors.push(error);
+                            attrs.clone()
+                        }
+                    };
+                    targets.push(Target::new(
+                        format_ident!("{}{}Builder", ident, variant.ident),
+                        format_ident!("builder_{}", snake_case(&variant.ident)),
+                        format_ident!("to_builder_{}", snake_case(&variant.ident)),
+                        Some(&variant.ident),
+                        attrs,
+                        &variant.fields,
+                    ));
+                }
+            }
+            Data::Union(data) => {
+                return Err(Error::new_spanned(
+                    data.union_token,
+                    "Builder does not support unions",
+                ))
+            }
+        }
+
+        let targets = targets
+            .into_iter()
+            .filter_map(|target| target.map_err(|error| errors.push(error)).ok())
+            .collect();
+
+        errors.finish(Input {
+            ident,
+            vis: &node.vis,
+            generics: &node.generics,
+            targets,
+        })
+    }
+}
+
+impl<'a> Target<'a> {
+    fn new(
+        builder: Ident,
+        ctor: Ident,
+        to_builder: Ident,
+        variant: Option<&'a Ident>,
+        attrs: ContainerAttrs,
+        fields: &'a Fields,
+    ) -> Result<Self> {
+        let mut errors = Errors::default();
+        let fields: Vec<Field> = fields
+            .iter()
+            .enumerate()
+            .filter_map(|(index, field)| {
+                Field::from_syn(field, index, &attrs)
+                    .map_err(|error| errors.push(error))
+                    .ok()
+            })
+            .collect();
+
+        // A renamed tuple field could otherwise collide with another one.
+        for (i, field) in fields.iter().enumerate() {
+            if fields[..i].iter().any(|other| other.ident == field.ident) {
+                errors.push(Error::new(
+                    field.ident.span(),
+                    format_args!("two fields of the builder are named `{}`", field.ident),
+                ));
+            }
+        }
+
+        // Every builder but a typestate one has a `merge` method.
+        if !attrs.typestate {
+            for field in &fields {
+                let setter = field
+                    .setter
+                    .as_ref()
+                    .map(|setter| (&setter.name, "`setter(name = \"...\")`"));
+                let each = match &field.kind {
+                    Kind::Repeated { each, .. } => Some((each, "another `each`")),
+                    _ => None,
+                };
+                for (name, rename) in setter.into_iter().chain(each) {
+                    if name.unraw() == "merge" {
+                        errors.push(Error::new(
+                            name.span(),
+                            format_args!(
+                                "a setter named `merge` clashes with the builder's `merge` method; rename it with {}",
+                                rename,
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+
+        // The error enum's variants are named after fields, and could clash
+        // with each other or with the ones that are not, as `fields` would
+        // with `missing = "all"`.
+        if !attrs.typestate || attrs.validate.is_some() {
+            let mut variants: Vec<(String, String)> = Vec::new();
+            if attrs.all_missing && !attrs.typestate {
+                variants.push(("MissingFields".to_owned(), "`missing = \"all\"`".to_owned()));
+            }
+            if attrs.validate.is_some() {
+                variants.push(("Invalid".to_owned(), "`validate`".to_owned()));
+            }
+            for field in &fields {
+                let variant = match field.kind {
+                    Kind::Nested { .. } => field.nested_variant(),
+                    _ if field.is_required() && !attrs.typestate => field.missing_variant(),
+                    _ => continue,
+                };
+                let variant = variant.to_string();
+                if let Some((_, owner)) = variants.iter().find(|(taken, _)| *taken == variant) {
+                    errors.push(Error::new(
+                        field.ident.span(),
+                        format_args!(
+                            "field `{}` needs the error variant `{}`, which is already taken by {}",
+                            field.ident.unraw(),
+                            variant,
+                            owner,
+                        ),
+                    ));
+                }
+                variants.push((variant, format!("field `{}`", field.ident.unraw())));
+            }
+        }
+
+        errors.finish(Target {
+            error: format_ident!("{}Error", builder),
+            builder,
+            ctor,
+            to_builder,
+            variant,
+            attrs,
+            fields,
+        })
+    }
+}
+
+impl<'a> Field<'a> {
+    /// Whether `build()` fails when the field was never set.
+    pub fn is_required(&self) -> bool {
+        match self.kind {
+            Kind::Required => self.default.is_none(),
+            Kind::Optional(_) | Kind::Repeated { .. } | Kind::Nested { .. } => false,
+        }
+    }
+
+    /// The variant of the builder error reporting this field as unset,
+    /// `current_dir` -> `MissingCurrentDir`.
+    pub fn missing_variant(&self) -> Ident {
+        format_ident!("Missing{}", self.camel_name(), span = self.ident.span())
+    }
+
+    /// The variant of the builder error wrapping the error of a nested
+    /// builder, `sandbox` -> `InvalidSandbox`.
+    pub fn nested_variant(&self) -> Ident {
+        format_ident!("Invalid{}", self.camel_name(), span = self.ident.span())
+    }
+
+    /// The field name in UpperCamelCase, `current_dir` -> `CurrentDir`.
+    pub fn camel_name(&self) -> String {
+        let name = self.ident.unraw().to_string();
+        let mut camel = String::new();
+        for word in name.split('_') {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                camel.extend(first.to_uppercase());
+                camel.push_str(chars.as_str());
+            }
+        }
+        camel
+    }
+
+    fn from_syn(node: &'a syn::Field, index: usize, container: &ContainerAttrs) -> Result<Self> {
+        let attrs = attr::field_attrs(&node.attrs)?;
+        let (ident, member) = match (&node.ident, attrs.name) {
+            (Some(ident), None) => (ident.clone(), Member::Named(ident.clone())),
+            (Some(_), Some(name)) => {
+                return Err(Error::new(
+                    name.span(),
+                    "`name` is for tuple fields; this field already has one",
+                ))
+            }
+            (None, name) => (
+                name.unwrap_or_else(|| format_ident!("_{}", index, span = node.ty.span())),
+                Member::Unnamed(syn::Index {
+                    index: index as u32,
+                    span: node.ty.span(),
+                }),
+            ),
+        };
+        let setter_attrs = attrs.setter;
+        let kind = if let Some(span) = attrs.sub_builder {
+            if container.typestate {
+                return Err(Error::new(
+                    span,
+                    "`sub_builder` cannot be used in a typestate builder, whose `build()` cannot fail",
+                ));
+            }
+            match ty::builder_of(&node.ty) {
+                Some((builder, error)) => Kind::Nested {
+                    builder: Box::new(builder),
+                    error: Box::new(error),
+                },
+                None => {
+                    return Err(Error::new_spanned(
+                        &node.ty,
+                        "`sub_builder` needs a type deriving `Builder`, such as `Sandbox`",
+                    ))
+                }
+            }
+        } else if let Some(each) = attrs.each {
+            Kind::Repeated {
+                each,
+                item: ty::collection_item(&node.ty),
+            }
+        } else if let Some(inner) = ty::option_inner(&node.ty) {
+            Kind::Optional(inner)
+        } else {
+            Kind::Required
+        };
+
+        let strip_option = match (setter_attrs.strip_option, &kind) {
+            (Some((_, strip)), Kind::Optional(_)) => strip,
+            (Some((span, _)), _) => {
+                return Err(Error::new(
+                    span,
+                    "`strip_option` only applies to fields of type `Option<T>`",
+                ))
+            }
+            (None, _) => true,
+        };
+
+        // Without a setter the field can only ever take its default.
+        let mut default = attrs.default;
+        let setter = match setter_attrs.skip {
+            Some(span) => {
+                if let Kind::Required = kind {
+                    default.get_or_insert(FieldDefault { span, expr: None });
+                }
+                None
+            }
+            None => Some(Setter {
+                name: setter_attrs.name.unwrap_or_else(|| ident.clone()),
+                vis: setter_attrs.vis.unwrap_or_else(|| parse_quote!(pub)),
+                strip_option,
+                try_setter: attrs.try_setter.is_some(),
+            }),
+        };
+
+        Ok(Field {
+            ident,
+            member,
+            ty: &node.ty,
+            kind,
+            default,
+            into: attrs.into.unwrap_or(container.into),
+            setter,
+        })
+    }
+}
+
+/// `GetStatus` -> `get_status`, `HTTPRequest` -> `http_request`.
+fn snake_case(ident: &Ident) -> String {
+    let name = ident.to_string();
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::new();
+    for (i, &ch) in chars.iter().enumerate() {
+        if ch.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || prev.is_uppercase() && next_is_lower
+            {
+                snake.push('_');
+            }
+        }
+        snake.extend(ch.to_lowercase());
+    }
+    snake
+}
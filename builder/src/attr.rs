@@ -0,0 +1,474 @@
+use proc_macro2::{Span, TokenTree};
+use quote::quote;
+use syn::meta::ParseNestedMeta;
+use syn::{Attribute, Error, Expr, Ident, LitBool, LitStr, Path, Result, Token, Type, Visibility};
+
+/// Options given to a struct, an enum, or one enum variant through
+/// `#[builder(...)]`. Variants start from the options of their enum.
+#[derive(Clone, Default)]
+pub struct ContainerAttrs {
+    /// `missing = "all"`: `build()` reports every unset field in one error
+    /// instead of stopping at the first.
+    pub all_missing: bool,
+    /// `validate = "path::to::fn"`, called by `build()` with the finished
+    /// value before it is returned.
+    pub validate: Option<Path>,
+    /// `validate_error = "Type"`, the error type of the `validate` function.
+    /// `String` when not given.
+    pub validate_error: Option<Type>,
+    /// `into`: every setter takes `impl Into<T>` unless the field opts out.
+    pub into: bool,
+    /// `pattern = "..."`, how setters and `build()` take the builder.
+    pub pattern: Option<Pattern>,
+    /// `typestate`: the builder's type tracks which required fields are set,
+    /// and `build()` only compiles once all of them are.
+    pub typestate: bool,
+    /// `no_std`: generated code takes `Vec` and `String` from `alloc` rather
+    /// than `std`.
+    pub no_std: bool,
+}
+
+impl ContainerAttrs {
+    pub fn pattern(&self) -> Pattern {
+        if self.typestate {
+            // Every required setter changes the builder's type, which needs
+            // the builder by value.
+            return Pattern::Owned;
+        }
+        self.pattern.unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Pattern {
+    /// Setters take and return `&mut self`; `build()` clones, leaving the
+    /// builder as it was.
+    #[default]
+    Mutable,
+    /// Setters and `build()` take `self` by value.
+    Owned,
+    /// Setters take `&self` and return an updated clone; `build()` clones.
+    Immutable,
+}
+
+/// Options given to a single field through `#[builder(...)]`.
+#[derive(Default)]
+pub struct FieldAttrs {
+    /// Name of the one-element-at-a-time setter, from `each = "..."`.
+    pub each: Option<Ident>,
+    /// Value used by `build()` when the field was never set.
+    pub default: Option<FieldDefault>,
+    /// `into` or `into = false`, overriding the container's `into`.
+    pub into: Option<bool>,
+    /// `name = "..."`, the setter and builder field of a tuple field in place
+    /// of `_0`, `_1`, ...
+    pub name: Option<Ident>,
+    /// `setter(...)`, how the field's setter looks.
+    pub setter: SetterAttrs,
+    /// `try_setter`: a `try_` setter converting its argument with `TryInto`
+    /// is added next to the setter.
+    pub try_setter: Option<Span>,
+    /// `sub_builder`: the field is built by its own builder, which the
+    /// field's accessor hands out to be edited in place. That builder can
+    /// use any pattern but cannot be a typestate builder, which has no one
+    /// type to store.
+    pub sub_builder: Option<Span>,
+}
+
+/// Options given inside `#[builder(setter(...))]`.
+#[derive(Default)]
+pub struct SetterAttrs {
+    /// `name = "..."`, the setter's name in place of the field's.
+    pub name: Option<Ident>,
+    /// `skip`: no setter at all, so the field always takes its default.
+    pub skip: Option<Span>,
+    /// `strip_option = false`: the setter of an `Option<T>` field takes the
+    /// whole `Option<T>`, so a value can be cleared again.
+    pub strip_option: Option<(Span, bool)>,
+    /// `vis = "..."`, the setter's visibility in place of `pub`.
+    pub vis: Option<Visibility>,
+}
+
+pub struct FieldDefault {
+    pub span: Span,
+    /// `None` for a bare `default`, which falls back to `Default::default()`.
+    pub expr: Option<Expr>,
+}
+
+const CONTAINER_KEYS: &[&str] = &[
+    "into",
+    "missing",
+    "no_std",
+    "pattern",
+    "typestate",
+    "validate",
+    "validate_error",
+];
+
+const FIELD_KEYS: &[&str] = &[
+    "default",
+    "each",
+    "into",
+    "name",
+    "setter",
+    "sub_builder",
+    "try_setter",
+];
+
+const SETTER_KEYS: &[&str] = &["name", "skip", "strip_option", "vis"];
+
+pub fn container_attrs(attrs: &[Attribute], inherited: &ContainerAttrs) -> Result<ContainerAttrs> {
+    let mut container = inherited.clone();
+    let mut errors = Errors::default();
+
+    let misplaced = (FIELD_KEYS, "a field");
+    parse_keys(
+        attrs,
+        CONTAINER_KEYS,
+        misplaced,
+        &mut errors,
+        |key, meta| {
+            match key {
+                "missing" => {
+                    if container.typestate {
+                        return Err(meta.error("`missing` has no effect on a typestate builder"));
+                    }
+                    let mode: LitStr = meta.value()?.parse()?;
+                    container.all_missing = match mode.value().as_str() {
+                        "first" => false,
+                        "all" => true,
+                        _ => return Err(Error::new(mode.span(), "expected \"first\" or \"all\"")),
+                    };
+                }
+                "into" => container.into = flag(meta)?,
+                "no_std" => container.no_std = flag(meta)?,
+                "pattern" => {
+                    let pattern: LitStr = meta.value()?.parse()?;
+                    let pattern = match pattern.value().as_str() {
+                        "mutable" => Pattern::Mutable,
+                        "owned" => Pattern::Owned,
+                        "immutable" => Pattern::Immutable,
+                        _ => {
+                            return Err(Error::new(
+                                pattern.span(),
+                                "expected \"mutable\", \"owned\" or \"immutable\"",
+                            ))
+                        }
+                    };
+                    if container.typestate && pattern != Pattern::Owned {
+                        return Err(meta.error("typestate builders always use the owned pattern"));
+                    }
+                    container.pattern = Some(pattern);
+                }
+                "typestate" => {
+                    if container
+                        .pattern
+                        .is_some_and(|pattern| pattern != Pattern::Owned)
+                    {
+                        return Err(meta.error("typestate builders always use the owned pattern"));
+                    }
+                    if container.all_missing {
+                        return Err(meta.error("`missing` has no effect on a typestate builder"));
+                    }
+                    container.typestate = flag(meta)?;
+                }
+                "validate" => {
+                    let path: LitStr = meta.value()?.parse()?;
+                    container.validate = Some(path.parse()?);
+                }
+                "validate_error" => {
+                    let ty: LitStr = meta.value()?.parse()?;
+                    container.validate_error = Some(ty.parse()?);
+                }
+                _ => unreachable!(),
+            }
+            Ok(())
+        },
+    );
+
+    if let (None, Some(ty)) = (&container.validate, &container.validate_error) {
+        errors.push(Error::new_spanned(
+            ty,
+            "`validate_error` requires `validate`",
+        ));
+    }
+
+    errors.finish(container)
+}
+
+pub fn field_attrs(attrs: &[Attribute]) -> Result<FieldAttrs> {
+    let mut field = FieldAttrs::default();
+    let mut errors = Errors::default();
+
+    let misplaced = (CONTAINER_KEYS, "the struct, enum or variant");
+    parse_keys(attrs, FIELD_KEYS, misplaced, &mut errors, |key, meta| {
+        match key {
+            "each" => {
+                if field.each.is_some() {
+                    return Err(meta.error("duplicate `each` attribute"));
+                }
+                let name: LitStr = meta.value()?.parse()?;
+                field.each = Some(name.parse()?);
+            }
+            "default" => {
+                if field.default.is_some() {
+                    return Err(meta.error("duplicate `default` attribute"));
+                }
+                let expr = if meta.input.peek(Token![=]) {
+                    let expr: LitStr = meta.value()?.parse()?;
+                    Some(expr.parse()?)
+                } else {
+                    None
+                };
+                field.default = Some(FieldDefault {
+                    span: meta.path.get_ident().unwrap().span(),
+                    expr,
+                });
+            }
+            "into" => {
+                if field.into.is_some() {
+                    return Err(meta.error("duplicate `into` attribute"));
+                }
+                field.into = Some(flag(meta)?);
+            }
+            "name" => {
+                if field.name.is_some() {
+                    return Err(meta.error("duplicate `name` attribute"));
+                }
+                let name: LitStr = meta.value()?.parse()?;
+                field.name = Some(name.parse()?);
+            }
+            "try_setter" => {
+                if field.try_setter.is_some() {
+                    return Err(meta.error("duplicate `try_setter` attribute"));
+                }
+                if flag(meta)? {
+                    field.try_setter = Some(meta.path.get_ident().unwrap().span());
+                }
+            }
+            "sub_builder" => {
+                if field.sub_builder.is_some() {
+                    return Err(meta.error("duplicate `sub_builder` attribute"));
+                }
+                if flag(meta)? {
+                    field.sub_builder = Some(meta.path.get_ident().unwrap().span());
+                }
+            }
+            "setter" => meta.parse_nested_meta(|meta| {
+                let setter = &mut field.setter;
+                let key = key_of(&meta);
+                match key.as_str() {
+                    "name" if setter.name.is_none() => {
+                        let name: LitStr = meta.value()?.parse()?;
+                        setter.name = Some(name.parse()?);
+                    }
+                    "skip" if setter.skip.is_none() => {
+                        if flag(&meta)? {
+                            setter.skip = Some(meta.path.get_ident().unwrap().span());
+                        }
+                    }
+                    "strip_option" if setter.strip_option.is_none() => {
+                        let span = meta.path.get_ident().unwrap().span();
+                        setter.strip_option = Some((span, flag(&meta)?));
+                    }
+                    "vis" if setter.vis.is_none() => {
+                        let vis: LitStr = meta.value()?.parse()?;
+                        setter.vis = Some(vis.parse()?);
+                    }
+                    _ if SETTER_KEYS.contains(&key.as_str()) => {
+                        return Err(meta.error(format_args!("duplicate setter option `{}`", key)));
+                    }
+                    _ => return Err(unknown_key(&meta, "setter option", SETTER_KEYS)),
+                }
+                Ok(())
+            })?,
+            _ => unreachable!(),
+        }
+        Ok(())
+    });
+
+    if let (Some(_), Some(default)) = (&field.each, &field.default) {
+        errors.push(Error::new(
+            default.span,
+            "`default` cannot be combined with `each`, which already starts out empty",
+        ));
+    }
+    if let (Some(each), Some(span)) = (&field.each, field.setter.skip) {
+        errors.push(Error::new(
+            span,
+            format_args!("`skip` would also remove the `{}` setter of `each`", each),
+        ));
+    }
+
+    if field.sub_builder.is_some() {
+        let conflicts = [
+            ("each", field.each.as_ref().map(Ident::span)),
+            (
+                "default",
+                field.default.as_ref().map(|default| default.span),
+            ),
+            ("try_setter", field.try_setter),
+        ];
+        for (key, span) in conflicts {
+            if let Some(span) = span {
+                errors.push(Error::new(
+                    span,
+                    format_args!("`{}` cannot be combined with `sub_builder`", key),
+                ));
+            }
+        }
+    } else if let Some(span) = field.try_setter {
+        if field.each.is_some() {
+            errors.push(Error::new(
+                span,
+                "`try_setter` cannot be combined with `each`",
+            ));
+        } else if field.setter.skip.is_some() {
+            errors.push(Error::new(
+                span,
+                "`try_setter` needs a setter, but it is skipped",
+            ));
+        }
+    }
+
+    errors.finish(field)
+}
+
+/// Calls `parse` with every key of every `#[builder(...)]` attribute that is
+/// one of `keys`. Unknown keys and errors returned by `parse` are collected
+/// rather than ending the parse, so every mistake on an item is reported at
+/// once.
+///
+/// `misplaced` are the keys accepted in the other position, field or
+/// container, and where that is; they get a more helpful error than an
+/// unknown key.
+fn parse_keys(
+    attrs: &[Attribute],
+    keys: &[&str],
+    misplaced: (&[&str], &str),
+    errors: &mut Errors,
+    mut parse: impl FnMut(&str, &ParseNestedMeta) -> Result<()>,
+) {
+    for attr in attrs {
+        if !attr.path().is_ident("builder") {
+            continue;
+        }
+
+        let result = attr.parse_nested_meta(|meta| {
+            let key = key_of(&meta);
+            let result = if keys.contains(&key.as_str()) {
+                parse(&key, &meta)
+            } else if misplaced.0.contains(&key.as_str()) {
+                Err(meta.error(format_args!("`{}` belongs on {}", key, misplaced.1)))
+            } else {
+                Err(unknown_key(&meta, "builder attribute", keys))
+            };
+            if let Err(error) = result {
+                errors.push(error);
+                skip_value(&meta)?;
+            }
+            Ok(())
+        });
+
+        if let Err(error) = result {
+            errors.push(error);
+        }
+    }
+}
+
+fn key_of(meta: &ParseNestedMeta) -> String {
+    meta.path
+        .get_ident()
+        .map(Ident::to_string)
+        .unwrap_or_default()
+}
+
+/// An error for a key that is not one of `keys`, suggesting the closest one
+/// when it looks like a typo.
+fn unknown_key(meta: &ParseNestedMeta, what: &str, keys: &[&str]) -> Error {
+    let path = &meta.path;
+    let key = quote!(#path).to_string().replace(' ', "");
+    let expected = keys
+        .iter()
+        .map(|key| format!("`{}`", key))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut message = format!("unknown {} `{}`, expected one of {}", what, key, expected,);
+    let closest = keys
+        .iter()
+        .map(|candidate| (edit_distance(&key, candidate), candidate))
+        .min();
+    if let Some((distance, candidate)) = closest {
+        if distance <= key.len().max(3) / 3 {
+            message += &format!("; did you mean `{}`?", candidate);
+        }
+    }
+    Error::new_spanned(path, message)
+}
+
+/// Levenshtein distance, counting insertions, deletions and substitutions.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Consumes whatever follows a key up to the next comma, so that parsing can
+/// go on with the next key after an error.
+fn skip_value(meta: &ParseNestedMeta) -> Result<()> {
+    meta.input.step(|cursor| {
+        let mut rest = *cursor;
+        while let Some((tt, next)) = rest.token_tree() {
+            match &tt {
+                TokenTree::Punct(punct) if punct.as_char() == ',' => break,
+                _ => rest = next,
+            }
+        }
+        Ok(((), rest))
+    })
+}
+
+/// Errors collected while parsing, reported together.
+#[derive(Default)]
+pub struct Errors {
+    combined: Option<Error>,
+}
+
+impl Errors {
+    pub fn push(&mut self, error: Error) {
+        match &mut self.combined {
+            Some(combined) => combined.combine(error),
+            None => self.combined = Some(error),
+        }
+    }
+
+    pub fn finish<T>(self, value: T) -> Result<T> {
+        match self.combined {
+            Some(error) => Err(error),
+            None => Ok(value),
+        }
+    }
+}
+
+/// A boolean option, either bare (`into`) or explicit (`into = false`).
+fn flag(meta: &ParseNestedMeta) -> Result<bool> {
+    if meta.input.peek(Token![=]) {
+        let value: LitBool = meta.value()?.parse()?;
+        Ok(value.value)
+    } else {
+        Ok(true)
+    }
+}
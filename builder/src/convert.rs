@@ -0,0 +1,170 @@
+//! Going back from a built value to a builder holding all of its fields.
+
+use crate::ast::{Input, Kind, Target};
+use crate::expand;
+use crate::typestate;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Member;
+
+/// The builder type a populated builder has: for a typestate builder, the one
+/// with every required field set.
+fn builder_type(input: &Input, target: &Target) -> TokenStream {
+    if target.attrs.typestate {
+        typestate::all_set(input, target)
+    } else {
+        let builder = &target.builder;
+        let (_, ty_generics, _) = input.generics.split_for_impl();
+        quote!(#builder #ty_generics)
+    }
+}
+
+/// A builder literal taking every field from a binding of the same name,
+/// after passing it through `value`.
+fn populate(
+    input: &Input,
+    target: &Target,
+    value: impl Fn(TokenStream) -> TokenStream,
+) -> TokenStream {
+    let builder = &target.builder;
+    let fields = target.fields.iter().map(|field| {
+        let ident = &field.ident;
+        let value = value(quote!(#ident));
+        match field.kind {
+            Kind::Required => quote!(#ident: ::core::option::Option::Some(#value)),
+            Kind::Optional(_) | Kind::Repeated { .. } => quote!(#ident: #value),
+            Kind::Nested { .. } => quote!(#ident: ::core::convert::From::from(#value)),
+        }
+    });
+    let marker = expand::marker(input).map(|_| quote!(__phantom: ::core::marker::PhantomData,));
+    let state = if target.attrs.typestate {
+        Some(quote!(__state: ::core::marker::PhantomData,))
+    } else {
+        None
+    };
+    quote! {
+        #builder {
+            #(#fields,)*
+            #marker
+            #state
+        }
+    }
+}
+
+/// The pattern destructuring the struct or variant into one binding per field,
+/// named like the builder's field.
+fn pattern(input: &Input, target: &Target) -> TokenStream {
+    let ident = input.ident;
+    let fields = target.fields.iter().map(|field| {
+        let binding = &field.ident;
+        match &field.member {
+            Member::Named(_) => quote!(#binding),
+            Member::Unnamed(index) => quote!(#index: #binding),
+        }
+    });
+    match target.variant {
+        Some(variant) => quote!(#ident::#variant { #(#fields,)* }),
+        None => quote!(#ident { #(#fields,)* }),
+    }
+}
+
+/// `to_builder`, or `to_builder_<variant>` for an enum, added next to the
+/// function creating an empty builder.
+pub fn to_builder(input: &Input, target: &Target) -> TokenStream {
+    let vis = input.vis;
+    let method = &target.to_builder;
+    let builder_type = builder_type(input, target);
+    let pattern = pattern(input, target);
+    let populate = populate(
+        input,
+        target,
+        |ident| quote!(::core::clone::Clone::clone(#ident)),
+    );
+
+    // The bounds are higher-ranked so that they are checked where the method
+    // is called instead of making the whole impl fail for fields that are not
+    // `Clone`.
+    let bounds = target.fields.iter().map(|field| {
+        let ty = field.ty;
+        quote!(for<'__a> #ty: ::core::clone::Clone)
+    });
+
+    match target.variant {
+        None => {
+            let doc = format!(
+                "Copies every field of `self` into a new [`{}`].",
+                target.builder,
+            );
+            quote! {
+                #[doc = #doc]
+                #vis fn #method(&self) -> #builder_type
+                where
+                    #(#bounds,)*
+                {
+                    let #pattern = self;
+                    #populate
+                }
+            }
+        }
+        Some(_) => {
+            let doc = format!(
+                "Copies every field of `self` into a new [`{}`], or returns \
+                 `None` if `self` is a different variant.",
+                target.builder,
+            );
+            quote! {
+                #[doc = #doc]
+                #vis fn #method(&self) -> ::core::option::Option<#builder_type>
+                where
+                    #(#bounds,)*
+                {
+                    match self {
+                        #pattern => ::core::option::Option::Some(#populate),
+                        #[allow(unreachable_patterns)]
+                        _ => ::core::option::Option::None,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// `From<Command> for CommandBuilder`, or for an enum variant a `TryFrom`
+/// that hands back values of the other variants.
+pub fn from_impl(input: &Input, target: &Target) -> TokenStream {
+    let ident = input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let builder_type = builder_type(input, target);
+    let pattern = pattern(input, target);
+    let populate = populate(input, target, |ident| ident);
+
+    match target.variant {
+        None => quote! {
+            impl #impl_generics ::core::convert::From<#ident #ty_generics> for #builder_type
+            #where_clause
+            {
+                fn from(value: #ident #ty_generics) -> Self {
+                    let #pattern = value;
+                    #populate
+                }
+            }
+        },
+        Some(_) => quote! {
+            impl #impl_generics ::core::convert::TryFrom<#ident #ty_generics> for #builder_type
+            #where_clause
+            {
+                type Error = #ident #ty_generics;
+
+                fn try_from(
+                    value: #ident #ty_generics,
+                ) -> ::core::result::Result<Self, Self::Error> {
+                    match value {
+                        #pattern => ::core::result::Result::Ok(#populate),
+                        #[allow(unreachable_patterns)]
+                        other => ::core::result::Result::Err(other),
+                    }
+                }
+            }
+        },
+    }
+}
@@ -0,0 +1,1019 @@
+use crate::ast::{Field, Input, Kind, Setter, Target};
+use crate::attr::Pattern;
+use crate::convert;
+use crate::ty::Item;
+use crate::typestate;
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{parse_quote, DeriveInput, Ident, Result, Type, Visibility};
+
+pub fn derive(node: &DeriveInput) -> Result<TokenStream> {
+    let input = Input::from_syn(node)?;
+    let ident = input.ident;
+    let vis = input.vis;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let marker_init = marker(&input).map(|_| quote!(__phantom: ::core::marker::PhantomData,));
+    let ctors = input.targets.iter().map(|target| {
+        let ctor = &target.ctor;
+        let builder = &target.builder;
+        let inits = target.fields.iter().map(field_init);
+        let state_init = if target.attrs.typestate {
+            Some(quote!(__state: ::core::marker::PhantomData,))
+        } else {
+            None
+        };
+        // For a typestate builder the state parameters are left to their
+        // defaults, which mark every required field as unset.
+        quote! {
+            #vis fn #ctor() -> #builder #ty_generics {
+                #builder {
+                    #(#inits,)*
+                    #marker_init
+                    #state_init
+                }
+            }
+        }
+    });
+
+    let to_builders = input
+        .targets
+        .iter()
+        .map(|target| convert::to_builder(&input, target));
+
+    let builders = input
+        .targets
+        .iter()
+        .map(|target| expand_builder(&input, target));
+
+    let from_impls = input
+        .targets
+        .iter()
+        .map(|target| convert::from_impl(&input, target));
+
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #(#ctors)*
+            #(#to_builders)*
+        }
+
+        #(#builders)*
+        #(#from_impls)*
+    })
+}
+
+/// The type of a field that ties every generic parameter to the builder, since
+/// the fields of an enum variant need not mention all of them.
+pub fn marker(input: &Input) -> Option<TokenStream> {
+    if input.generics.params.is_empty() {
+        return None;
+    }
+    let ident = input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    Some(quote!(::core::marker::PhantomData<fn() -> #ident #ty_generics>))
+}
+
+fn expand_builder(input: &Input, target: &Target) -> TokenStream {
+    let ident = input.ident;
+    let vis = input.vis;
+    let builder = &target.builder;
+    let typestate = target.attrs.typestate;
+    let generics = if typestate {
+        typestate::generics(input, target)
+    } else {
+        input.generics.clone()
+    };
+    let (impl_generics, builder_generics, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let state_marker = if typestate {
+        Some(typestate::marker(target))
+    } else {
+        None
+    };
+
+    // A deserialized typestate builder could claim any state, so those are
+    // left out.
+    let serde = cfg!(feature = "serde") && !typestate;
+    let (derive_deserialize, serde_default, serde_skip) = if serde {
+        // The bounds are higher-ranked so that a builder whose fields cannot
+        // be deserialized still compiles, only without the impl.
+        let bounds = target.fields.iter().map(|field| {
+            let ty = storage_type(field);
+            quote!(for<'__a> #ty: ::serde::Deserialize<'de>)
+        });
+        let bound = quote!(#(#bounds,)*).to_string();
+        (
+            Some(quote! {
+                #[derive(::serde::Deserialize)]
+                #[serde(bound(deserialize = #bound))]
+            }),
+            Some(quote!(#[serde(default)])),
+            Some(quote!(#[serde(skip)])),
+        )
+    } else {
+        (None, None, None)
+    };
+
+    let storage = target.fields.iter().map(|field| {
+        let ident = &field.ident;
+        let ty = storage_type(field);
+        quote!(#serde_default #ident: #ty)
+    });
+    let marker = marker(input).map(|marker| quote!(#serde_skip __phantom: #marker,));
+
+    let pattern = target.attrs.pattern();
+    let setters = target.fields.iter().map(|field| {
+        if typestate && field.is_required() {
+            typestate::setter(input, target, field)
+        } else {
+            setter(pattern, field)
+        }
+    });
+
+    // A typestate builder cannot reach `build()` with a required field unset.
+    let checks = if typestate {
+        None
+    } else {
+        Some(expand_checks(target))
+    };
+
+    let nested = build_nested(target);
+    let values = target.fields.iter().map(|field| {
+        let member = &field.member;
+        let value = build_value(field);
+        quote!(#member: #value)
+    });
+
+    let error = &target.error;
+    let error_type = if typestate && target.attrs.validate.is_none() {
+        None
+    } else {
+        Some(expand_error(input, target))
+    };
+
+    let path = match target.variant {
+        Some(variant) => quote!(#ident::#variant),
+        None => quote!(#ident),
+    };
+
+    // Only a typestate builder is consumed by the build, and has nothing to
+    // put the fields back into.
+    let validate = target.attrs.validate.as_ref().map(|validate| {
+        let restore = if typestate {
+            None
+        } else {
+            Some(restore(target, &path))
+        };
+        quote! {
+            if let ::core::result::Result::Err(error) = #validate(&value) {
+                #restore
+                return ::core::result::Result::Err(#error::Invalid(error));
+            }
+        }
+    });
+
+    // Merging would have to change the state of a typestate builder.
+    let merge = if typestate {
+        None
+    } else {
+        Some(merge(pattern, target))
+    };
+
+    let (build_output, build_result) = if error_type.is_some() {
+        (
+            quote!(::core::result::Result<#ident #ty_generics, #error>),
+            quote!(::core::result::Result::Ok(value)),
+        )
+    } else {
+        (quote!(#ident #ty_generics), quote!(value))
+    };
+
+    let build_in_place = quote! {
+        #checks
+        #nested
+        let value = #path {
+            #(#values,)*
+        };
+        #validate
+        #build_result
+    };
+
+    // Every builder but a typestate one is built in place by
+    // `__build_sub_builder`, which a nested builder is built through too. A
+    // borrowed builder is built from a clone so that it is left as it was.
+    let build_clone = quote!(::core::clone::Clone::clone(self).__build_sub_builder());
+    let (build_receiver, build_where, build_body) = match pattern {
+        Pattern::Owned if typestate => (
+            quote!(mut self),
+            Some(typestate::build_bounds(target)),
+            build_in_place.clone(),
+        ),
+        Pattern::Mutable => (
+            quote!(&mut self),
+            Some(quote!(where for<'__a> Self: ::core::clone::Clone)),
+            build_clone,
+        ),
+        Pattern::Owned => (quote!(mut self), None, quote!(self.__build_sub_builder())),
+        Pattern::Immutable => (
+            quote!(&self),
+            Some(quote!(where for<'__a> Self: ::core::clone::Clone)),
+            build_clone,
+        ),
+    };
+
+    // A typestate builder has one type per state, and only the empty one
+    // could be a default.
+    let (typestate_items, default_impl) = if typestate {
+        (Some(typestate::items(input, target)), None)
+    } else {
+        let ctor = &target.ctor;
+        let default_impl = quote! {
+            impl #impl_generics ::core::default::Default for #builder #builder_generics
+            #where_clause
+            {
+                fn default() -> Self {
+                    <#ident #ty_generics>::#ctor()
+                }
+            }
+        };
+        (None, Some(default_impl))
+    };
+
+    // Every builder but a typestate one is `Clone` when its fields are, which
+    // a borrowed builder needs to be built, and to hold it as a nested one.
+    let clone_impl = if typestate {
+        None
+    } else {
+        Some(clone_impl(input, target))
+    };
+
+    // A typestate builder has no single type to nest.
+    let sub_builder_methods = if typestate {
+        None
+    } else {
+        Some(sub_builder_methods(
+            pattern,
+            target,
+            &build_output,
+            &build_in_place,
+        ))
+    };
+
+    // A typestate builder only reaches `build()` with every required field
+    // set, so without `validate` it cannot fail.
+    let mut failures = Vec::new();
+    if !typestate && target.fields.iter().any(Field::is_required) {
+        failures.push("a required field was never set");
+    }
+    if target
+        .fields
+        .iter()
+        .any(|field| matches!(field.kind, Kind::Nested { .. }))
+    {
+        failures.push("a nested builder fails");
+    }
+    if target.attrs.validate.is_some() {
+        failures.push("the value does not validate");
+    }
+    let build_doc = match failures.split_last() {
+        None if typestate => {
+            "Builds the value. Only available once every required field is set, so it cannot fail."
+                .to_owned()
+        }
+        None => "Builds the value.".to_owned(),
+        Some((last, [])) => format!("Builds the value, failing if {}.", last),
+        Some((last, rest)) => format!(
+            "Builds the value, failing if {} or {}.",
+            rest.join(", "),
+            last
+        ),
+    };
+
+    quote! {
+        #derive_deserialize
+        #vis struct #builder #generics #where_clause {
+            #(#storage,)*
+            #marker
+            #state_marker
+        }
+
+        impl #impl_generics #builder #builder_generics #where_clause {
+            #(#setters)*
+            #merge
+            #sub_builder_methods
+
+            #[doc = #build_doc]
+            pub fn build(#build_receiver) -> #build_output
+            #build_where
+            {
+                #build_body
+            }
+        }
+
+        #default_impl
+        #clone_impl
+        #error_type
+        #typestate_items
+    }
+}
+
+/// Higher-ranked for the same reason as in `to_builder`: a builder with fields
+/// that are not `Clone` still compiles, only without the impl.
+fn clone_impl(input: &Input, target: &Target) -> TokenStream {
+    let builder = &target.builder;
+    let mut generics = input.generics.clone();
+    let where_clause = generics.make_where_clause();
+    for field in &target.fields {
+        let ty = storage_type(field);
+        where_clause
+            .predicates
+            .push(parse_quote!(for<'__a> #ty: ::core::clone::Clone));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let fields = target.fields.iter().map(|field| {
+        let ident = &field.ident;
+        quote!(#ident: ::core::clone::Clone::clone(&self.#ident))
+    });
+    let marker_init = marker(input).map(|_| quote!(__phantom: ::core::marker::PhantomData,));
+    quote! {
+        impl #impl_generics ::core::clone::Clone for #builder #ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                #builder {
+                    #(#fields,)*
+                    #marker_init
+                }
+            }
+        }
+    }
+}
+
+/// Puts the fields of a `value` that failed validation back into a builder
+/// built in place, so that it can be fixed and built again. Fields that were
+/// left to their default come back set to it.
+fn restore(target: &Target, path: &TokenStream) -> TokenStream {
+    let bindings: Vec<Ident> = (0..target.fields.len())
+        .map(|i| format_ident!("value{}", i, span = Span::mixed_site()))
+        .collect();
+    let fields = target.fields.iter().zip(&bindings).map(|(field, binding)| {
+        let member = &field.member;
+        quote!(#member: #binding)
+    });
+    let stores = target.fields.iter().zip(&bindings).map(|(field, binding)| {
+        let ident = &field.ident;
+        let value = match field.kind {
+            Kind::Required => quote!(::core::option::Option::Some(#binding)),
+            Kind::Optional(_) | Kind::Repeated { .. } => quote!(#binding),
+            Kind::Nested { .. } => quote!(::core::convert::From::from(#binding)),
+        };
+        quote!(self.#ident = #value;)
+    });
+    // The value is always the variant being built, but other variants would
+    // make the pattern refutable.
+    let other = target.variant.map(|_| {
+        quote! {
+            #[allow(unreachable_patterns)]
+            _ => ::core::unreachable!(),
+        }
+    });
+    quote! {
+        match value {
+            #path { #(#fields,)* .. } => {
+                #(#stores)*
+            }
+            #other
+        }
+    }
+}
+
+/// The crate `Vec` and `String` are taken from: `alloc` for a `no_std`
+/// builder, which needs `extern crate alloc` in the crate root.
+fn alloc(target: &Target) -> TokenStream {
+    if target.attrs.no_std {
+        quote!(::alloc)
+    } else {
+        quote!(::std)
+    }
+}
+
+/// How generated methods reach the builder they update: `self` itself, or for
+/// the immutable pattern a local clone of it.
+pub fn this(pattern: Pattern) -> TokenStream {
+    match pattern {
+        Pattern::Mutable | Pattern::Owned => quote!(self),
+        Pattern::Immutable => Ident::new("builder", Span::mixed_site()).into_token_stream(),
+    }
+}
+
+fn expand_checks(target: &Target) -> TokenStream {
+    let error = &target.error;
+    let alloc = alloc(target);
+    let required = target.fields.iter().filter(|field| field.is_required());
+
+    if target.attrs.all_missing {
+        let pushes = required.map(|field| {
+            let ident = &field.ident;
+            let variant = field.missing_variant();
+            quote! {
+                if ::core::option::Option::is_none(&self.#ident) {
+                    #alloc::vec::Vec::push(&mut missing, #error::#variant);
+                }
+            }
+        });
+        quote! {
+            let mut missing = #alloc::vec::Vec::new();
+            #(#pushes)*
+            if !#alloc::vec::Vec::is_empty(&missing) {
+                return ::core::result::Result::Err(#error::MissingFields(missing));
+            }
+        }
+    } else {
+        let checks = required.map(|field| {
+            let ident = &field.ident;
+            let variant = field.missing_variant();
+            quote! {
+                if ::core::option::Option::is_none(&self.#ident) {
+                    return ::core::result::Result::Err(#error::#variant);
+                }
+            }
+        });
+        quote!(#(#checks)*)
+    }
+}
+
+fn expand_error(input: &Input, target: &Target) -> TokenStream {
+    let vis = input.vis;
+    let error = &target.error;
+    let alloc = alloc(target);
+    let required: Vec<&Field> = if target.attrs.typestate {
+        Vec::new()
+    } else {
+        target.fields.iter().filter(|f| f.is_required()).collect()
+    };
+
+    let variants = required.iter().map(|field| {
+        let variant = field.missing_variant();
+        let doc = format!("`{}` was never set.", field.ident.unraw());
+        quote! {
+            #[doc = #doc]
+            #variant
+        }
+    });
+
+    let nested: Vec<&Field> = target
+        .fields
+        .iter()
+        .filter(|field| matches!(field.kind, Kind::Nested { .. }))
+        .collect();
+
+    let nested_variants = nested.iter().map(|field| {
+        let variant = field.nested_variant();
+        let doc = format!("Building `{}` failed.", field.ident.unraw());
+        let ty = match &field.kind {
+            Kind::Nested { error, .. } => error,
+            _ => unreachable!(),
+        };
+        quote! {
+            #[doc = #doc]
+            #variant(#ty)
+        }
+    });
+
+    // Errors of nested builders are shown with the path of fields leading to
+    // them, `missing: sandbox.allow_net`, so the message is put together by a
+    // method that the enclosing builder's error can pass that path to.
+    let path = Ident::new("path", Span::mixed_site());
+    let name = Ident::new("name", Span::mixed_site());
+    let write_name = if required.is_empty() {
+        None
+    } else {
+        Some(quote! {
+            let #name = |formatter: &mut ::core::fmt::Formatter, name: &str| {
+                if let ::core::option::Option::Some(path) = #path {
+                    ::core::write!(formatter, "{}.", path)?;
+                }
+                formatter.write_str(name)
+            };
+        })
+    };
+
+    let displays = required.iter().map(|field| {
+        let variant = field.missing_variant();
+        let field_name = field.ident.unraw().to_string();
+        quote! {
+            #error::#variant => {
+                formatter.write_str("missing: ")?;
+                #name(formatter, #field_name)
+            }
+        }
+    });
+
+    let nested_displays = nested.iter().map(|field| {
+        let variant = field.nested_variant();
+        let field_name = field.ident.unraw().to_string();
+        quote! {
+            #error::#variant(ref error) => match #path {
+                ::core::option::Option::Some(path) => {
+                    let path = ::core::format_args!("{}.{}", path, #field_name);
+                    error.__fmt_at(::core::option::Option::Some(&path), formatter)
+                }
+                ::core::option::Option::None => {
+                    error.__fmt_at(::core::option::Option::Some(&#field_name), formatter)
+                }
+            }
+        }
+    });
+
+    let (all_missing_variant, all_missing_display) = if target.attrs.all_missing {
+        let names = required.iter().map(|field| {
+            let variant = field.missing_variant();
+            let field_name = field.ident.unraw().to_string();
+            quote!(#error::#variant => #field_name)
+        });
+        (
+            Some(quote! {
+                /// Every required field that was never set, in declaration
+                /// order.
+                MissingFields(#alloc::vec::Vec<#error>),
+            }),
+            Some(quote! {
+                #error::MissingFields(ref missing) => {
+                    formatter.write_str("missing: ")?;
+                    for (i, error) in missing.iter().enumerate() {
+                        if i > 0 {
+                            formatter.write_str(", ")?;
+                        }
+                        #name(formatter, match *error {
+                            #(#names,)*
+                            _ => ::core::unreachable!(),
+                        })?;
+                    }
+                    ::core::result::Result::Ok(())
+                }
+            }),
+        )
+    } else {
+        (None, None)
+    };
+
+    let (invalid_variant, invalid_display) = if target.attrs.validate.is_some() {
+        let ty = match &target.attrs.validate_error {
+            Some(ty) => quote!(#ty),
+            None => quote!(#alloc::string::String),
+        };
+        (
+            Some(quote! {
+                /// The built value was rejected by its `validate` function.
+                Invalid(#ty),
+            }),
+            Some(quote! {
+                #error::Invalid(ref error) => {
+                    if let ::core::option::Option::Some(path) = #path {
+                        ::core::write!(formatter, "{}: ", path)?;
+                    }
+                    ::core::fmt::Display::fmt(error, formatter)
+                }
+            }),
+        )
+    } else {
+        (None, None)
+    };
+
+    let description = format!("The error returned by [`{}::build`].", target.builder);
+
+    quote! {
+        #[doc = #description]
+        #[derive(Debug)]
+        #vis enum #error {
+            #(#variants,)*
+            #all_missing_variant
+            #invalid_variant
+            #(#nested_variants,)*
+        }
+
+        impl #error {
+            #[doc(hidden)]
+            #[allow(unused_variables)]
+            pub fn __fmt_at(
+                &self,
+                #path: ::core::option::Option<&dyn ::core::fmt::Display>,
+                formatter: &mut ::core::fmt::Formatter,
+            ) -> ::core::fmt::Result {
+                #write_name
+                match *self {
+                    #(#displays,)*
+                    #all_missing_display
+                    #invalid_display
+                    #(#nested_displays,)*
+                }
+            }
+        }
+
+        impl ::core::fmt::Display for #error {
+            fn fmt(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                self.__fmt_at(::core::option::Option::None, formatter)
+            }
+        }
+
+        impl ::core::error::Error for #error {}
+    }
+}
+
+/// Builds every nested builder into a local before anything is taken out of
+/// the builder, so that a failing one leaves the other fields in place and
+/// the nested builders built before it are put back.
+fn build_nested(target: &Target) -> TokenStream {
+    let error = &target.error;
+    let nested: Vec<&Field> = target
+        .fields
+        .iter()
+        .filter(|field| matches!(field.kind, Kind::Nested { .. }))
+        .collect();
+    let builds = nested.iter().enumerate().map(|(i, field)| {
+        let ident = &field.ident;
+        let local = nested_local(field);
+        let variant = field.nested_variant();
+        let build = quote_spanned!(field.ty.span()=> __build_sub_builder);
+        let restores = nested[..i].iter().map(|built| {
+            let ident = &built.ident;
+            let local = nested_local(built);
+            quote!(self.#ident = ::core::convert::From::from(#local);)
+        });
+        quote! {
+            let #local = match self.#ident.#build() {
+                ::core::result::Result::Ok(value) => value,
+                ::core::result::Result::Err(error) => {
+                    #(#restores)*
+                    return ::core::result::Result::Err(#error::#variant(error));
+                }
+            };
+        }
+    });
+    quote!(#(#builds)*)
+}
+
+fn nested_local(field: &Field) -> Ident {
+    format_ident!("nested_{}", field.ident.unraw(), span = Span::mixed_site())
+}
+
+fn build_value(field: &Field) -> TokenStream {
+    let ident = &field.ident;
+    let taken = match field.kind {
+        Kind::Required | Kind::Optional(_) => {
+            quote!(::core::option::Option::take(&mut self.#ident))
+        }
+        Kind::Repeated { .. } => return quote!(::core::mem::take(&mut self.#ident)),
+        Kind::Nested { .. } => return nested_local(field).into_token_stream(),
+    };
+
+    let default = match &field.default {
+        Some(default) => match &default.expr {
+            Some(expr) => quote!(#expr),
+            None => quote!(::core::default::Default::default()),
+        },
+        None => {
+            return match field.kind {
+                Kind::Optional(_) => taken,
+                _ => quote!(::core::option::Option::unwrap(#taken)),
+            };
+        }
+    };
+
+    // The default is only evaluated when the field was never set.
+    let some = match field.kind {
+        Kind::Optional(_) => quote!(::core::option::Option::Some(value)),
+        _ => quote!(value),
+    };
+    quote! {
+        match #taken {
+            ::core::option::Option::Some(value) => #some,
+            ::core::option::Option::None => #default,
+        }
+    }
+}
+
+fn storage_type(field: &Field) -> TokenStream {
+    let ty = field.ty;
+    match field.kind {
+        Kind::Required => quote!(::core::option::Option<#ty>),
+        Kind::Optional(inner) => quote!(::core::option::Option<#inner>),
+        Kind::Repeated { .. } => quote!(#ty),
+        Kind::Nested { ref builder, .. } => quote!(#builder),
+    }
+}
+
+fn field_init(field: &Field) -> TokenStream {
+    let ident = &field.ident;
+    match field.kind {
+        Kind::Required | Kind::Optional(_) => quote!(#ident: ::core::option::Option::None),
+        Kind::Repeated { .. } | Kind::Nested { .. } => {
+            quote!(#ident: ::core::default::Default::default())
+        }
+    }
+}
+
+fn setter(pattern: Pattern, field: &Field) -> TokenStream {
+    let setter = match &field.setter {
+        Some(setter) => setter,
+        None => return TokenStream::new(),
+    };
+    let ident = &field.ident;
+    let name = &setter.name;
+    let vis = &setter.vis;
+    let this = this(pattern);
+    match &field.kind {
+        // Whatever the pattern, the nested builder is edited in place.
+        Kind::Nested { builder, .. } => quote! {
+            #vis fn #name(&mut self) -> &mut #builder {
+                &mut self.#ident
+            }
+        },
+        Kind::Required | Kind::Optional(_) => {
+            // Without `strip_option` the parameter is the field's own
+            // `Option<T>`, which is stored as is.
+            let (ty, value) = match field.kind {
+                Kind::Optional(_) if !setter.strip_option => param(field, name, field.ty),
+                Kind::Optional(inner) => {
+                    let (ty, value) = param(field, name, inner);
+                    (ty, quote!(::core::option::Option::Some(#value)))
+                }
+                _ => {
+                    let (ty, value) = param(field, name, field.ty);
+                    (ty, quote!(::core::option::Option::Some(#value)))
+                }
+            };
+            let assign = quote!(#this.#ident = #value;);
+            let method = setter_method(pattern, vis, name, None, quote!(#name: #ty), None, assign);
+            let try_method = if setter.try_setter {
+                let (receiver, ret, bound) = match pattern {
+                    Pattern::Mutable => (quote!(&mut self), quote!(&mut Self), None),
+                    Pattern::Owned => (quote!(self), quote!(Self), None),
+                    Pattern::Immutable => (
+                        quote!(&self),
+                        quote!(Self),
+                        Some(quote!(for<'__a> Self: ::core::clone::Clone,)),
+                    ),
+                };
+                let ty = match field.kind {
+                    Kind::Optional(inner) if setter.strip_option => inner,
+                    _ => field.ty,
+                };
+                Some(try_setter(setter, receiver, ret, bound, ty))
+            } else {
+                None
+            };
+            quote! {
+                #method
+                #try_method
+            }
+        }
+        Kind::Repeated { each, item } => {
+            let one = each_setter(pattern, vis, field, each, item);
+            // A one-at-a-time setter with the same name replaces the
+            // all-at-once one, since the two cannot share a name.
+            if each == name {
+                return one;
+            }
+            let (ty, value) = param(field, name, field.ty);
+            let assign = quote!(#this.#ident = #value;);
+            let all = setter_method(pattern, vis, name, None, quote!(#name: #ty), None, assign);
+            quote! {
+                #one
+                #all
+            }
+        }
+    }
+}
+
+fn each_setter(
+    pattern: Pattern,
+    vis: &Visibility,
+    field: &Field,
+    each: &Ident,
+    item: &Item,
+) -> TokenStream {
+    let ident = &field.ident;
+    let ty = field.ty;
+    let this = this(pattern);
+    let extend = |element: TokenStream| {
+        quote! {
+            ::core::iter::Extend::extend(&mut #this.#ident, ::core::iter::once(#element));
+        }
+    };
+    match *item {
+        Item::One(item) => {
+            let (item, value) = param(field, each, item);
+            let store = extend(value);
+            setter_method(pattern, vis, each, None, quote!(#each: #item), None, store)
+        }
+        Item::Pair(key_ty, value_ty) => {
+            let key = Ident::new("key", Span::call_site());
+            let value = Ident::new("value", Span::call_site());
+            let (key_ty, key_value) = param(field, &key, key_ty);
+            let (value_ty, value_value) = param(field, &value, value_ty);
+            let store = extend(quote!((#key_value, #value_value)));
+            let params = quote!(#key: #key_ty, #value: #value_ty);
+            setter_method(pattern, vis, each, None, params, None, store)
+        }
+        Item::Inferred => {
+            // Without a visible element type the setter accepts anything the
+            // collection knows how to be extended with.
+            let store = extend(quote!(#each));
+            setter_method(
+                pattern,
+                vis,
+                each,
+                Some(quote!(<__Item>)),
+                quote!(#each: __Item),
+                Some(quote!(#ty: ::core::iter::Extend<__Item>)),
+                store,
+            )
+        }
+    }
+}
+
+/// `merge`, taking every field `other` has a value for, so that layers of
+/// configuration can be stacked before `build()` checks the result. Repeated
+/// fields are extended with the elements of `other`, and nested builders are
+/// merged in turn.
+fn merge(pattern: Pattern, target: &Target) -> TokenStream {
+    let this = this(pattern);
+    let other = Ident::new("other", Span::call_site());
+    let fields = target.fields.iter().map(|field| {
+        let ident = &field.ident;
+        match field.kind {
+            Kind::Required | Kind::Optional(_) => quote! {
+                if let ::core::option::Option::Some(value) = #other.#ident {
+                    #this.#ident = ::core::option::Option::Some(value);
+                }
+            },
+            Kind::Repeated { .. } => quote! {
+                ::core::iter::Extend::extend(&mut #this.#ident, #other.#ident);
+            },
+            Kind::Nested { .. } => quote! {
+                #this.#ident.__merge_sub_builder(#other.#ident);
+            },
+        }
+    });
+    let bounds = merge_bounds(target);
+    let vis: Visibility = parse_quote!(pub);
+    let name = Ident::new("merge", Span::call_site());
+    let store = quote!(#(#fields)*);
+    let params = quote!(#other: Self);
+    setter_method(
+        pattern,
+        &vis,
+        &name,
+        None,
+        params,
+        Some(quote!(#(#bounds)*)),
+        store,
+    )
+}
+
+/// Higher-ranked for the same reason as in `to_builder`: a collection that
+/// cannot be merged only makes `merge` uncallable.
+fn merge_bounds(target: &Target) -> Vec<TokenStream> {
+    target
+        .fields
+        .iter()
+        .filter_map(|field| match field.kind {
+            Kind::Repeated { .. } => {
+                let ty = field.ty;
+                Some(quote! {
+                    for<'__a> #ty: ::core::iter::IntoIterator
+                        + ::core::iter::Extend<<#ty as ::core::iter::IntoIterator>::Item>,
+                })
+            }
+            Kind::Required | Kind::Optional(_) | Kind::Nested { .. } => None,
+        })
+        .collect()
+}
+
+/// What an enclosing builder calls for a `sub_builder` field. Both take
+/// `&mut self` whatever the pattern of this builder, so an owned or
+/// immutable builder can be nested as well as a mutable one. The build runs
+/// in place: required fields are checked and nested builders built before
+/// anything is taken out, and a failed validation puts the fields back, so
+/// the builder is only emptied by a build that succeeds.
+fn sub_builder_methods(
+    pattern: Pattern,
+    target: &Target,
+    build_output: &TokenStream,
+    build_in_place: &TokenStream,
+) -> TokenStream {
+    let bounds = merge_bounds(target);
+    let other = Ident::new("other", Span::call_site());
+    let merge = match pattern {
+        Pattern::Mutable => quote!(self.merge(#other);),
+        Pattern::Immutable => quote!(*self = self.merge(#other);),
+        Pattern::Owned => quote!(*self = ::core::mem::take(self).merge(#other);),
+    };
+    let clone_bound = match pattern {
+        Pattern::Immutable => Some(quote!(for<'__a> Self: ::core::clone::Clone,)),
+        Pattern::Mutable | Pattern::Owned => None,
+    };
+    quote! {
+        #[doc(hidden)]
+        pub fn __build_sub_builder(&mut self) -> #build_output {
+            #build_in_place
+        }
+
+        #[doc(hidden)]
+        pub fn __merge_sub_builder(&mut self, #other: Self)
+        where
+            #clone_bound
+            #(#bounds)*
+        {
+            #merge
+        }
+    }
+}
+
+/// Wraps the statement storing a setter's arguments into a method taking and
+/// returning the builder the way `pattern` asks for.
+fn setter_method(
+    pattern: Pattern,
+    vis: &Visibility,
+    name: &Ident,
+    generics: Option<TokenStream>,
+    params: TokenStream,
+    bound: Option<TokenStream>,
+    store: TokenStream,
+) -> TokenStream {
+    match pattern {
+        Pattern::Mutable => quote! {
+            #vis fn #name #generics(&mut self, #params) -> &mut Self
+            where
+                #bound
+            {
+                #store
+                self
+            }
+        },
+        Pattern::Owned => quote! {
+            #vis fn #name #generics(mut self, #params) -> Self
+            where
+                #bound
+            {
+                #store
+                self
+            }
+        },
+        Pattern::Immutable => {
+            let this = this(pattern);
+            quote! {
+                #vis fn #name #generics(&self, #params) -> Self
+                where
+                    for<'__a> Self: ::core::clone::Clone,
+                    #bound
+                {
+                    let mut #this = ::core::clone::Clone::clone(self);
+                    #store
+                    #this
+                }
+            }
+        }
+    }
+}
+
+/// `try_<setter>`, which converts its argument to `ty` with `TryInto` and
+/// hands it to the setter, so that a failed conversion is reported right away
+/// rather than by `build()`.
+pub fn try_setter(
+    setter: &Setter,
+    receiver: TokenStream,
+    ret: TokenStream,
+    bound: Option<TokenStream>,
+    ty: &Type,
+) -> TokenStream {
+    let Setter { name, vis, .. } = setter;
+    let try_name = format_ident!("try_{}", name.unraw());
+    quote! {
+        #vis fn #try_name<__Value>(
+            #receiver,
+            #name: __Value,
+        ) -> ::core::result::Result<#ret, <__Value as ::core::convert::TryInto<#ty>>::Error>
+        where
+            __Value: ::core::convert::TryInto<#ty>,
+            #bound
+        {
+            let #name = ::core::convert::TryInto::try_into(#name)?;
+            ::core::result::Result::Ok(self.#name(#name))
+        }
+    }
+}
+
+/// The parameter type of a setter accepting a `ty`, and the expression turning
+/// the parameter `ident` into a `ty`.
+pub fn param(field: &Field, ident: &Ident, ty: &Type) -> (TokenStream, TokenStream) {
+    if field.into {
+        (
+            quote!(impl ::core::convert::Into<#ty>),
+            quote!(::core::convert::Into::into(#ident)),
+        )
+    } else {
+        (quote!(#ty), quote!(#ident))
+    }
+}
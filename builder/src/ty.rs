@@ -0,0 +1,85 @@
+use quote::format_ident;
+use syn::{GenericArgument, Ident, Path, PathArguments, Type, TypePath};
+
+/// What the one-at-a-time setter of an `each` field accepts.
+pub enum Item<'a> {
+    /// A single element, `T` for `Vec<T>`, `HashSet<T>` and the like.
+    One(&'a Type),
+    /// A key and a value, for `HashMap<K, V>`, `BTreeMap<K, V>` and the like.
+    Pair(&'a Type, &'a Type),
+    /// Anything the collection can be extended with, for types such as
+    /// `String` whose element type does not appear in the tokens.
+    Inferred,
+}
+
+/// Returns `T` if `ty` is written as `Option<T>`.
+pub fn option_inner(ty: &Type) -> Option<&Type> {
+    match type_args(ty) {
+        Some((ident, args)) if ident == "Option" && args.len() == 1 => Some(args[0]),
+        _ => None,
+    }
+}
+
+/// Guesses the element type of a `Default + Extend` collection from the way
+/// it is written. Types whose name ends in `Map` are extended with key-value
+/// pairs; otherwise the first type argument is the element.
+pub fn collection_item(ty: &Type) -> Item<'_> {
+    match type_args(ty) {
+        Some((ident, args)) if ident.to_string().ends_with("Map") && args.len() >= 2 => {
+            Item::Pair(args[0], args[1])
+        }
+        Some((_, args)) if !args.is_empty() => Item::One(args[0]),
+        _ => Item::Inferred,
+    }
+}
+
+/// The builder and builder error derived for `ty`, named after its last path
+/// segment: `config::Sandbox<T>` has `config::SandboxBuilder<T>` and
+/// `config::SandboxBuilderError`.
+pub fn builder_of(ty: &Type) -> Option<(Type, Path)> {
+    let path = match ty {
+        Type::Path(ty) if ty.qself.is_none() => &ty.path,
+        _ => return None,
+    };
+
+    let mut builder = path.clone();
+    let segment = builder.segments.last_mut()?;
+    segment.ident = format_ident!("{}Builder", segment.ident);
+
+    let mut error = builder.clone();
+    let segment = error.segments.last_mut()?;
+    segment.ident = format_ident!("{}Error", segment.ident);
+    segment.arguments = PathArguments::None;
+
+    let builder = Type::Path(TypePath {
+        qself: None,
+        path: builder,
+    });
+    Some((builder, error))
+}
+
+// Only the tokens of the type are available to a derive macro, so this looks
+// at the last path segment by name, the same way `Option<T>` and
+// `std::option::Option<T>` both end in `Option<T>`.
+fn type_args(ty: &Type) -> Option<(&Ident, Vec<&Type>)> {
+    let path = match ty {
+        Type::Path(ty) if ty.qself.is_none() => &ty.path,
+        _ => return None,
+    };
+
+    let segment = path.segments.last()?;
+    let args = match &segment.arguments {
+        PathArguments::AngleBracketed(bracketed) => bracketed
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        PathArguments::None => Vec::new(),
+        PathArguments::Parenthesized(_) => return None,
+    };
+
+    Some((&segment.ident, args))
+}
@@ -0,0 +1,203 @@
+//! Code specific to `#[builder(typestate)]`.
+//!
+//! The builder gets one extra type parameter per required field, which is
+//! either `CommandBuilderUnset` or `CommandBuilderSet`. Setting a required
+//! field moves the builder into the `Set` state for that field, and `build()`
+//! requires every state to implement a per-field trait that only the `Set`
+//! marker implements. That trait carries a diagnostic naming the field, so
+//! calling `build()` too early is a compile error pointing out what is missing.
+
+use crate::ast::{Field, Input, Setter, Target};
+use crate::expand::{self, param};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, GenericParam, Generics, Ident};
+
+struct State<'t, 'a> {
+    field: &'t Field<'a>,
+    /// The builder's type parameter holding this field's state.
+    param: Ident,
+    /// The trait implemented only by the `Set` marker.
+    bound: Ident,
+}
+
+fn states<'t, 'a>(target: &'t Target<'a>) -> Vec<State<'t, 'a>> {
+    target
+        .fields
+        .iter()
+        .filter(|field| field.is_required())
+        .map(|field| State {
+            field,
+            param: format_ident!("__{}", field.camel_name()),
+            bound: format_ident!("{}Has{}", target.builder, field.camel_name()),
+        })
+        .collect()
+}
+
+// A required field always has a setter: skipping it gives the field a default.
+fn field_setter<'t>(field: &'t Field) -> &'t Setter {
+    field.setter.as_ref().unwrap()
+}
+
+fn set(target: &Target) -> Ident {
+    format_ident!("{}Set", target.builder)
+}
+
+fn unset(target: &Target) -> Ident {
+    format_ident!("{}Unset", target.builder)
+}
+
+/// The input's generics followed by one state parameter per required field,
+/// each starting out unset.
+pub fn generics(input: &Input, target: &Target) -> Generics {
+    let mut generics = input.generics.clone();
+    let unset = unset(target);
+    for state in states(target) {
+        let param = &state.param;
+        generics.params.push(parse_quote!(#param = #unset));
+    }
+    generics
+}
+
+/// The field holding the state parameters, which no other field mentions.
+pub fn marker(target: &Target) -> TokenStream {
+    let params = states(target).into_iter().map(|state| state.param);
+    quote!(__state: ::core::marker::PhantomData<fn() -> (#(#params,)*)>)
+}
+
+/// The marker types and per-field traits shared by every state of a builder.
+pub fn items(input: &Input, target: &Target) -> TokenStream {
+    let vis = input.vis;
+    let builder = &target.builder;
+    let set = set(target);
+    let unset = unset(target);
+
+    let bounds = states(target).into_iter().map(|state| {
+        let bound = &state.bound;
+        let name = &state.field.ident;
+        let message = format!("`{}` is missing a value for `{}`", builder, name);
+        let label = format!(
+            "call `.{}(...)` before `.build()`",
+            field_setter(state.field).name
+        );
+        quote! {
+            #[doc(hidden)]
+            #[diagnostic::on_unimplemented(message = #message, label = #label)]
+            #vis trait #bound {}
+
+            impl #bound for #set {}
+        }
+    });
+
+    let set_doc = format!("A required field of [`{}`] that has been set.", builder);
+    let unset_doc = format!("A required field of [`{}`] that is not set yet.", builder);
+
+    quote! {
+        #[doc = #set_doc]
+        #vis struct #set {}
+
+        #[doc = #unset_doc]
+        #vis struct #unset {}
+
+        #(#bounds)*
+    }
+}
+
+/// The setter of a required field, which returns the builder with that
+/// field's state changed to set.
+pub fn setter(input: &Input, target: &Target, field: &Field) -> TokenStream {
+    let builder = &target.builder;
+    let set = set(target);
+    let ident = &field.ident;
+    let Setter { name, vis, .. } = field_setter(field);
+    let (ty, value) = param(field, name, field.ty);
+
+    let args = args(input);
+    let states = states(target).into_iter().map(|state| {
+        if state.field.ident == *ident {
+            quote!(#set)
+        } else {
+            let param = state.param;
+            quote!(#param)
+        }
+    });
+
+    let moves = target.fields.iter().map(|other| {
+        let other = &other.ident;
+        if other == ident {
+            quote!(#other: ::core::option::Option::Some(#value))
+        } else {
+            quote!(#other: self.#other)
+        }
+    });
+    let phantom = expand::marker(input).map(|_| quote!(__phantom: self.__phantom,));
+
+    let ret = quote!(#builder<#(#args,)* #(#states,)*>);
+    let try_method = if field_setter(field).try_setter {
+        let setter = field_setter(field);
+        Some(expand::try_setter(
+            setter,
+            quote!(self),
+            ret.clone(),
+            None,
+            field.ty,
+        ))
+    } else {
+        None
+    };
+
+    quote! {
+        #try_method
+
+        #vis fn #name(self, #name: #ty) -> #ret {
+            #builder {
+                #(#moves,)*
+                #phantom
+                __state: ::core::marker::PhantomData,
+            }
+        }
+    }
+}
+
+/// The builder with every required field set, as produced from an existing
+/// value.
+pub fn all_set(input: &Input, target: &Target) -> TokenStream {
+    let builder = &target.builder;
+    let args = args(input);
+    let set = set(target);
+    let states = states(target).into_iter().map(|_| &set);
+    quote!(#builder<#(#args,)* #(#states,)*>)
+}
+
+/// The input's generic parameters as arguments, without bounds or defaults.
+fn args(input: &Input) -> Vec<TokenStream> {
+    input
+        .generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                quote!(#lifetime)
+            }
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                quote!(#ident)
+            }
+            GenericParam::Const(param) => {
+                let ident = &param.ident;
+                quote!(#ident)
+            }
+        })
+        .collect()
+}
+
+/// The where-clause of `build()`, requiring every required field to be set.
+pub fn build_bounds(target: &Target) -> TokenStream {
+    let bounds = states(target).into_iter().map(|state| {
+        let param = state.param;
+        let bound = state.bound;
+        quote!(#param: #bound)
+    });
+    quote!(where #(#bounds,)*)
+}
@@ -0,0 +1,68 @@
+// Enums get one builder per variant. Each builder is reached through an
+// associated function named after the variant in snake case, and follows the
+// same rules for required, `Option` and `#[builder(each = "...")]` fields as
+// the builder of a struct.
+//
+//     impl Message {
+//         pub fn builder_request() -> MessageRequestBuilder { ... }
+//         pub fn builder_response() -> MessageResponseBuilder { ... }
+//     }
+//
+// The `build` method of every variant builder returns the enum itself.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Message {
+    Request {
+        id: u64,
+        method: String,
+        #[builder(each = "param")]
+        params: Vec<String>,
+        timeout: Option<u32>,
+    },
+    Response {
+        id: u64,
+        body: String,
+    },
+    GetStatus {},
+}
+
+fn main() {
+    let request = Message::builder_request()
+        .id(1)
+        .method("status".to_owned())
+        .param("verbose".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        request,
+        Message::Request {
+            id: 1,
+            method: "status".to_owned(),
+            params: vec!["verbose".to_owned()],
+            timeout: None,
+        }
+    );
+
+    let response = Message::builder_response()
+        .id(1)
+        .body("ok".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        response,
+        Message::Response {
+            id: 1,
+            body: "ok".to_owned(),
+        }
+    );
+
+    assert!(Message::builder_response().id(2).build().is_err());
+    assert_eq!(
+        Message::builder_get_status().build().unwrap(),
+        Message::GetStatus {}
+    );
+}
@@ -0,0 +1,55 @@
+// Fields marked #[builder(default)] may be left unset, in which case build()
+// falls back to Default::default(). With #[builder(default = "...")] the string
+// is parsed as an expression that supplies the value instead.
+//
+// The default is evaluated lazily inside build(), only for fields that were
+// never set, so an expensive or side-effecting default runs at most once per
+// build and not at all when the caller provided the field.
+
+use derive_builder::Builder;
+use std::cell::Cell;
+
+thread_local! {
+    static CALLS: Cell<usize> = Cell::new(0);
+}
+
+fn default_dir() -> String {
+    CALLS.with(|calls| calls.set(calls.get() + 1));
+    "/tmp".to_owned()
+}
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(default)]
+    args: Vec<String>,
+    #[builder(each = "env")]
+    env: Vec<String>,
+    #[builder(default = "default_dir()")]
+    current_dir: String,
+    #[builder(default = "Some(3)")]
+    retries: Option<u32>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .unwrap();
+    assert!(command.args.is_empty());
+    assert_eq!(command.current_dir, "/tmp");
+    assert_eq!(command.retries, Some(3));
+    assert_eq!(CALLS.with(Cell::get), 1);
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .args(vec!["build".to_owned()])
+        .current_dir("..".to_owned())
+        .retries(0)
+        .build()
+        .unwrap();
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.current_dir, "..");
+    assert_eq!(command.retries, Some(0));
+    assert_eq!(CALLS.with(Cell::get), 1);
+}
@@ -0,0 +1,16 @@
+// A repeated field already starts out as an empty Vec, so asking for a default
+// on it is a mistake. The error should point at the `default` key rather than
+// at the whole field.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg", default)]
+    args: Vec<String>,
+    #[builder(default, default = "String::new()")]
+    current_dir: String,
+}
+
+fn main() {}
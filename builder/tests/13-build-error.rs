@@ -0,0 +1,77 @@
+// Instead of a boxed error, build() returns a generated error enum named after
+// the builder, with one variant per field that has to be set.
+//
+//     pub enum CommandBuilderError {
+//         MissingExecutable,
+//         MissingCurrentDir,
+//     }
+//
+// The enum implements Display and std::error::Error, so it still works with `?`
+// into Box<dyn Error>.
+//
+// By default build() stops at the first missing field. With
+// #[builder(missing = "all")] on the struct, it reports every missing field at
+// once through a MissingFields variant.
+
+use derive_builder::Builder;
+use std::error::Error;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    env: Vec<String>,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(missing = "all")]
+pub struct Process {
+    executable: String,
+    env: Vec<String>,
+    current_dir: String,
+    pid: Option<u32>,
+}
+
+fn run() -> Result<Command, Box<dyn Error>> {
+    Ok(Command::builder().build()?)
+}
+
+fn main() {
+    let err = Command::builder()
+        .env(vec![])
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(err, CommandBuilderError::MissingExecutable));
+    assert_eq!(err.to_string(), "missing: executable");
+
+    let err = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(err, CommandBuilderError::MissingEnv));
+
+    assert!(run().is_err());
+
+    let err = Process::builder()
+        .current_dir("..".to_owned())
+        .build()
+        .err()
+        .unwrap();
+    match &err {
+        ProcessBuilderError::MissingFields(missing) => {
+            assert!(matches!(
+                missing[..],
+                [
+                    ProcessBuilderError::MissingExecutable,
+                    ProcessBuilderError::MissingEnv,
+                ]
+            ));
+        }
+        _ => panic!("expected every missing field"),
+    }
+    assert_eq!(err.to_string(), "missing: executable, env");
+}
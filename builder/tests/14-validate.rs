@@ -0,0 +1,83 @@
+// A struct-level #[builder(validate = "path::to::fn")] names a function taking
+// the finished value by reference. build() calls it after every default has
+// been applied and turns an Err into the Invalid variant of the builder error.
+//
+// The validation function returns Result<(), String> unless another error type
+// is given with #[builder(validate_error = "...")].
+//
+// A failed validation leaves a borrowed builder as it was, so that it can be
+// fixed and built again.
+
+use derive_builder::Builder;
+use std::fmt::{self, Display};
+
+#[derive(Builder)]
+#[builder(validate = "check_command")]
+pub struct Command {
+    executable: String,
+    #[builder(default = "\"..\".to_owned()")]
+    current_dir: String,
+}
+
+fn check_command(command: &Command) -> Result<(), String> {
+    if command.executable.is_empty() {
+        return Err("executable must be non-empty".to_owned());
+    }
+    assert_eq!(command.current_dir, "..");
+    Ok(())
+}
+
+#[derive(Debug)]
+pub struct PortError(u16);
+
+impl Display for PortError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "port {} is reserved", self.0)
+    }
+}
+
+mod checks {
+    pub fn port(listener: &super::Listener) -> Result<(), super::PortError> {
+        if listener.port < 1024 {
+            return Err(super::PortError(listener.port));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Builder)]
+#[builder(validate = "checks::port", validate_error = "PortError")]
+pub struct Listener {
+    port: u16,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+
+    let err = Command::builder()
+        .executable(String::new())
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(&err, CommandBuilderError::Invalid(msg) if msg.contains("non-empty")));
+    assert_eq!(err.to_string(), "executable must be non-empty");
+
+    assert!(Command::builder().build().is_err());
+
+    let mut builder = Command::builder();
+    builder.executable(String::new());
+    assert!(builder.build().is_err());
+    builder.executable("cargo".to_owned());
+    let command = builder.build().unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.current_dir, "..");
+
+    let err = Listener::builder().port(80).build().err().unwrap();
+    assert!(matches!(err, ListenerBuilderError::Invalid(PortError(80))));
+    assert_eq!(err.to_string(), "port 80 is reserved");
+    assert_eq!(Listener::builder().port(8080).build().unwrap().port, 8080);
+}
@@ -0,0 +1,78 @@
+// Generic parameters, lifetimes and where-clauses of the input carry over to
+// the builder and to every impl block generated for it.
+//
+//     pub struct RequestBuilder<'a, B: Body> where B::Chunk: Clone { ... }
+//
+//     impl<'a, B: Body> Request<'a, B> where B::Chunk: Clone {
+//         pub fn builder() -> RequestBuilder<'a, B> { ... }
+//     }
+//
+// Only the bounds written by the user are used. In particular the builder does
+// not require `B: Default` or `B: Clone`, which the `Body` below implements
+// neither of. The default mutable pattern builds from a clone, so a builder
+// holding such a `B` is built with the owned pattern.
+
+use derive_builder::Builder;
+use std::marker::PhantomData;
+
+pub trait Body {
+    type Chunk;
+}
+
+pub struct Stream;
+
+impl Body for Stream {
+    type Chunk = u8;
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Request<'a, B: Body>
+where
+    B::Chunk: Clone,
+{
+    path: &'a str,
+    body: B,
+    #[builder(each = "chunk")]
+    chunks: Vec<B::Chunk>,
+    timeout: Option<u32>,
+}
+
+#[derive(Builder)]
+pub enum Frame<'a, T, const N: usize> {
+    Data { payload: &'a [T; N] },
+    Ping { id: u64 },
+}
+
+#[derive(Builder)]
+pub struct Tagged<T, U = ()> {
+    value: T,
+    tag: PhantomData<U>,
+}
+
+fn main() {
+    let path = String::from("/index.html");
+    let request = Request::builder()
+        .path(&path)
+        .body(Stream)
+        .chunk(b'a')
+        .chunk(b'b')
+        .build()
+        .unwrap();
+    assert_eq!(request.path, "/index.html");
+    assert_eq!(request.chunks, b"ab");
+    assert!(request.timeout.is_none());
+    let Stream = request.body;
+
+    let frame = Frame::<u8, 2>::builder_data().payload(&[1, 2]).build().unwrap();
+    assert!(matches!(frame, Frame::Data { payload: [1, 2] }));
+    let frame = Frame::<u8, 2>::builder_ping().id(7).build().unwrap();
+    assert!(matches!(frame, Frame::Ping { id: 7 }));
+
+    let tagged: Tagged<i32> = Tagged::builder()
+        .value(1)
+        .tag(PhantomData)
+        .build()
+        .unwrap();
+    assert_eq!(tagged.value, 1);
+}
@@ -0,0 +1,55 @@
+// With #[builder(into)] a setter takes `impl Into<T>` instead of `T`, and the
+// one-at-a-time setter of an `each` field takes `impl Into<Item>`. This removes
+// the `.to_owned()` calls needed to pass string literals to String fields.
+//
+// Put on the struct, `into` applies to every field. A field can opt back out
+// with #[builder(into = false)].
+
+use derive_builder::Builder;
+use std::path::PathBuf;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(into)]
+    executable: String,
+    #[builder(each = "arg", into)]
+    args: Vec<String>,
+    #[builder(into)]
+    current_dir: Option<PathBuf>,
+    timeout: u64,
+}
+
+#[derive(Builder)]
+#[builder(into)]
+pub struct Process {
+    executable: String,
+    #[builder(each = "env")]
+    env: Vec<(String, String)>,
+    #[builder(into = false)]
+    pid: u32,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo")
+        .arg("build")
+        .arg(String::from("--release"))
+        .current_dir("..")
+        .timeout(30)
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.current_dir, Some(PathBuf::from("..")));
+    assert_eq!(command.timeout, 30);
+
+    let process = Process::builder()
+        .executable("cargo")
+        .env((String::from("RUST_LOG"), String::from("debug")))
+        .pid(1)
+        .build()
+        .unwrap();
+    assert_eq!(process.executable, "cargo");
+    assert_eq!(process.env.len(), 1);
+    assert_eq!(process.pid, 1);
+}
@@ -0,0 +1,81 @@
+// #[builder(pattern = "...")] chooses how setters and build() take the builder.
+//
+//   - "mutable", the default: setters take and return &mut Self, and build()
+//     takes &mut self and clones what it needs, so the builder keeps its
+//     values and can be built again.
+//
+//   - "owned": setters and build() take self by value, so a builder can be
+//     moved through a chain of calls and fields need not implement Clone.
+//
+//   - "immutable": setters take &self and return an updated copy, so one
+//     partially filled builder can be the starting point of several values.
+//     build() takes &self and clones what it needs.
+
+use derive_builder::Builder;
+
+pub struct Socket {
+    fd: i32,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Connection {
+    socket: Socket,
+    #[builder(each = "header")]
+    headers: Vec<String>,
+    timeout: Option<u32>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable")]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+pub struct Request {
+    url: String,
+    #[builder(default)]
+    retries: u8,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub enum Event {
+    Open { socket: Socket },
+    Close { code: u16 },
+}
+
+fn make_connection(fd: i32) -> Connection {
+    let builder = Connection::builder().socket(Socket { fd });
+    builder.header("Host: localhost".to_owned()).build().unwrap()
+}
+
+fn main() {
+    let connection = make_connection(3);
+    assert_eq!(connection.socket.fd, 3);
+    assert_eq!(connection.headers, vec!["Host: localhost"]);
+    assert!(connection.timeout.is_none());
+
+    let cargo = Command::builder().executable("cargo".to_owned());
+    let build = cargo.arg("build".to_owned());
+    let test = cargo.arg("test".to_owned());
+    assert_eq!(build.build().unwrap().args, vec!["build"]);
+    assert_eq!(test.build().unwrap().args, vec!["test"]);
+    assert_eq!(build.build().unwrap().args, vec!["build"]);
+    assert!(cargo.build().unwrap().args.is_empty());
+
+    let mut request = Request::builder();
+    request.url("https://example.com".to_owned());
+    assert_eq!(request.build().unwrap().url, "https://example.com");
+    request.retries(3);
+    let second = request.build().unwrap();
+    assert_eq!(second.url, "https://example.com");
+    assert_eq!(second.retries, 3);
+
+    let event = Event::builder_open().socket(Socket { fd: 4 }).build().unwrap();
+    assert!(matches!(event, Event::Open { socket: Socket { fd: 4 } }));
+    assert!(Event::builder_close().build().is_err());
+}
@@ -0,0 +1,60 @@
+// #[builder(each = "...")] works on any collection that implements Default and
+// Extend, not only Vec. The builder stores the collection itself and extends
+// it by one element per call.
+//
+// Types whose name ends in Map, such as HashMap<K, V> and BTreeMap<K, V>, get a
+// setter taking the key and the value as two arguments. When the element type
+// is not visible in the tokens, as for String, the setter accepts anything the
+// collection can be extended with.
+
+use derive_builder::Builder;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: VecDeque<String>,
+    #[builder(each = "env", into)]
+    env: HashMap<String, String>,
+    #[builder(each = "limit")]
+    limits: BTreeMap<&'static str, u64>,
+    #[builder(each = "feature")]
+    features: HashSet<String>,
+    #[builder(each = "group")]
+    groups: BTreeSet<u32>,
+    #[builder(each = "flag")]
+    flags: String,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .env("RUST_LOG", "debug")
+        .env("RUST_BACKTRACE", "1")
+        .limit("cpu", 2)
+        .feature("serde".to_owned())
+        .feature("serde".to_owned())
+        .group(2)
+        .group(1)
+        .flag('-')
+        .flag("qv")
+        .build()
+        .unwrap();
+
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.env.len(), 2);
+    assert_eq!(command.env["RUST_LOG"], "debug");
+    assert_eq!(command.limits["cpu"], 2);
+    assert_eq!(command.features.len(), 1);
+    assert_eq!(command.groups.into_iter().collect::<Vec<_>>(), vec![1, 2]);
+    assert_eq!(command.flags, "-qv");
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .unwrap();
+    assert!(command.env.is_empty());
+    assert!(command.flags.is_empty());
+}
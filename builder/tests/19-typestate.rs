@@ -0,0 +1,59 @@
+// With #[builder(typestate)] the builder's type records which required fields
+// have been set, so build() can only be called once all of them are, and it
+// returns the value directly instead of a Result.
+//
+//     pub struct CommandBuilder<__Executable = CommandBuilderUnset> { ... }
+//
+// The setter of a required field moves the builder from the Unset to the Set
+// state for that field, so typestate builders always use the owned pattern.
+// Fields that are optional, repeated or have a default do not take part.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(default = "\"..\".to_owned()")]
+    current_dir: String,
+    timeout: Option<u32>,
+    pid: u32,
+}
+
+#[derive(Builder)]
+#[builder(typestate, validate = "check_port")]
+pub struct Listener<'a> {
+    host: &'a str,
+    port: u16,
+}
+
+fn check_port(listener: &Listener) -> Result<(), String> {
+    if listener.port == 0 {
+        return Err("port must be non-zero".to_owned());
+    }
+    Ok(())
+}
+
+fn main() {
+    let command = Command::builder()
+        .arg("build".to_owned())
+        .pid(7)
+        .executable("cargo".to_owned())
+        .timeout(30)
+        .build();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.current_dir, "..");
+    assert_eq!(command.timeout, Some(30));
+    assert_eq!(command.pid, 7);
+
+    let partial: CommandBuilder<CommandBuilderSet, CommandBuilderUnset> =
+        Command::builder().executable("cargo".to_owned());
+    assert_eq!(partial.pid(8).build().pid, 8);
+
+    let listener = Listener::builder().host("localhost").port(80).build().unwrap();
+    assert_eq!(listener.host, "localhost");
+    assert!(Listener::builder().host("localhost").port(0).build().is_err());
+}
@@ -0,0 +1,18 @@
+// Calling build() on a typestate builder before every required field is set
+// fails to compile, and the error names the field that is missing.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    current_dir: String,
+    timeout: Option<u32>,
+}
+
+fn main() {
+    let _command = Command::builder()
+        .current_dir("..".to_owned())
+        .build();
+}
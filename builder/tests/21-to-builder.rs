@@ -0,0 +1,102 @@
+// An existing value can be turned back into a builder with every field already
+// set, either by value through From or by cloning through to_builder(). This
+// makes "copy, change two fields, rebuild" a one-liner. Repeated fields keep
+// their elements, so further `each` calls append to them.
+//
+//     impl From<Command> for CommandBuilder { ... }
+//
+//     impl Command {
+//         pub fn to_builder(&self) -> CommandBuilder { ... }
+//     }
+//
+// For an enum, each variant builder implements TryFrom<Message>, handing back
+// values of other variants as the error, and Message::to_builder_<variant>()
+// returns None for other variants.
+//
+// to_builder() needs every field to be Clone, but only where it is called, so
+// types with fields that are not Clone can still derive Builder, and be built
+// with the owned pattern.
+
+use derive_builder::Builder;
+use std::convert::TryFrom;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Message {
+    Request { id: u64, method: String },
+    Response { id: u64 },
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Listener {
+    host: String,
+    port: u16,
+}
+
+pub struct Socket;
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Connection {
+    socket: Socket,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .build()
+        .unwrap();
+
+    let release = command
+        .to_builder()
+        .arg("--release".to_owned())
+        .current_dir("..".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(release.args, vec!["build", "--release"]);
+    assert_eq!(release.current_dir.as_deref(), Some(".."));
+    assert!(command.current_dir.is_none());
+
+    let test = CommandBuilder::from(command)
+        .executable("cargo-nextest".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(test.executable, "cargo-nextest");
+    assert_eq!(test.args, vec!["build"]);
+
+    let request = Message::builder_request()
+        .id(1)
+        .method("status".to_owned())
+        .build()
+        .unwrap();
+    let retry = request.to_builder_request().unwrap().id(2).build().unwrap();
+    assert_eq!(
+        retry,
+        Message::Request {
+            id: 2,
+            method: "status".to_owned(),
+        }
+    );
+    assert!(request.to_builder_response().is_none());
+    let request = MessageResponseBuilder::try_from(request).err().unwrap();
+    assert!(MessageRequestBuilder::try_from(request).is_ok());
+
+    let listener = Listener::builder()
+        .host("localhost".to_owned())
+        .port(80)
+        .build();
+    let listener = listener.to_builder().port(8080).build();
+    assert_eq!(listener.port, 8080);
+
+    let connection = Connection::builder().socket(Socket).build().unwrap();
+    let Socket = ConnectionBuilder::from(connection).build().unwrap().socket;
+}
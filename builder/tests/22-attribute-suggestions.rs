@@ -0,0 +1,22 @@
+// Every mistake in the `#[builder(...)]` attributes of one struct is reported
+// in a single pass. Unknown keys list the keys accepted in that position and
+// suggest the closest one when it is only a typo away, and keys that belong on
+// the container rather than a field (or the other way around) say so.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(patern = "owned", each = "arg")]
+pub struct Command {
+    executable: String,
+    #[builder(eech = "arg")]
+    args: Vec<String>,
+    #[builder(defualt, typestate)]
+    env: Vec<String>,
+    #[builder(frobnicate)]
+    current_dir: Option<String>,
+    #[builder(setter(skp))]
+    shell: String,
+}
+
+fn main() {}
@@ -0,0 +1,72 @@
+// Tuple structs, tuple variants and unit-like structs get builders too. The
+// setters of positional fields are named `_0`, `_1`, ... unless a field is
+// given a name with #[builder(name = "...")], which also names it in errors.
+// The usual rules apply: Option fields may be left unset, other fields must be
+// set unless they have a default.
+//
+//     impl RgbBuilder {
+//         pub fn red(&mut self, red: u8) -> &mut Self { ... }
+//         pub fn green(&mut self, green: u8) -> &mut Self { ... }
+//         pub fn blue(&mut self, blue: u8) -> &mut Self { ... }
+//     }
+//
+//     impl UserIdBuilder {
+//         pub fn _0(&mut self, _0: u64) -> &mut Self { ... }
+//     }
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Rgb(
+    #[builder(name = "red")] u8,
+    #[builder(name = "green")] u8,
+    #[builder(name = "blue", default)] u8,
+);
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct UserId(u64);
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Label(String, Option<String>);
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Marker;
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Shape {
+    Circle(f64),
+    Rect(#[builder(name = "width")] f64, #[builder(name = "height")] f64),
+    Empty,
+}
+
+fn main() {
+    let color = Rgb::builder().red(255).green(128).build().unwrap();
+    assert_eq!(color, Rgb(255, 128, 0));
+
+    let err = Rgb::builder().red(1).build().unwrap_err();
+    assert_eq!(err.to_string(), "missing: green");
+    assert!(matches!(err, RgbBuilderError::MissingGreen));
+
+    let id = UserId::builder()._0(7).build().unwrap();
+    assert_eq!(id, UserId(7));
+    assert!(matches!(
+        UserId::builder().build(),
+        Err(UserIdBuilderError::Missing0)
+    ));
+
+    let label = Label::builder()._0("x".to_owned()).build().unwrap();
+    assert_eq!(label, Label("x".to_owned(), None));
+
+    assert_eq!(Marker::builder().build().unwrap(), Marker);
+
+    let circle = Shape::builder_circle()._0(1.5).build().unwrap();
+    assert_eq!(circle, Shape::Circle(1.5));
+    let rect = Shape::builder_rect().width(2.0).height(3.0).build().unwrap();
+    assert_eq!(rect, Shape::Rect(2.0, 3.0));
+    assert_eq!(Shape::builder_empty().build().unwrap(), Shape::Empty);
+
+    let mut builder = RgbBuilder::from(color);
+    builder.blue(64);
+    assert_eq!(builder.build().unwrap(), Rgb(255, 128, 64));
+    assert_eq!(rect.to_builder_rect().unwrap().build().unwrap(), rect);
+}
@@ -0,0 +1,80 @@
+// #[builder(setter(...))] changes how a field's setter looks:
+//
+//   - name = "..." renames the setter; the builder error still names the
+//     field.
+//   - skip leaves the field without a setter, so it always takes its default
+//     (Default::default() unless the field has #[builder(default = "...")]).
+//   - strip_option = false makes the setter of an Option<T> field take the
+//     whole Option<T>, so a value can be cleared again.
+//   - vis = "..." gives the setter another visibility than `pub`; an empty
+//     string makes it private to the module.
+
+use derive_builder::Builder;
+
+mod request {
+    use derive_builder::Builder;
+
+    #[derive(Builder, Debug)]
+    pub struct Request {
+        pub url: String,
+        #[builder(setter(vis = ""))]
+        pub retries: Option<u32>,
+    }
+
+    impl RequestBuilder {
+        pub fn with_retries(&mut self) -> &mut Self {
+            self.retries(3)
+        }
+    }
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Command {
+    #[builder(setter(name = "program"))]
+    executable: String,
+    #[builder(setter(skip))]
+    runs: u32,
+    #[builder(setter(skip), default = "\"/tmp\".to_owned()")]
+    scratch_dir: String,
+    #[builder(setter(strip_option = false))]
+    current_dir: Option<String>,
+    #[builder(each = "arg", setter(vis = "pub(crate)", name = "set_args"))]
+    args: Vec<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .program("cargo".to_owned())
+        .current_dir(Some("..".to_owned()))
+        .current_dir(None)
+        .arg("build".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(
+        command,
+        Command {
+            executable: "cargo".to_owned(),
+            runs: 0,
+            scratch_dir: "/tmp".to_owned(),
+            current_dir: None,
+            args: vec!["build".to_owned()],
+        }
+    );
+
+    let command = Command::builder()
+        .program("cargo".to_owned())
+        .set_args(vec!["test".to_owned()])
+        .build()
+        .unwrap();
+    assert_eq!(command.args, ["test"]);
+
+    let err = Command::builder().build().unwrap_err();
+    assert_eq!(err.to_string(), "missing: executable");
+
+    let request = request::Request::builder()
+        .url("https://example.com".to_owned())
+        .with_retries()
+        .build()
+        .unwrap();
+    assert_eq!(request.retries, Some(3));
+}
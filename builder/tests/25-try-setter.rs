@@ -0,0 +1,84 @@
+// #[builder(try_setter)] adds a `try_` setter next to the normal one. It takes
+// anything that converts into the field's type with TryInto and fails right
+// away with the conversion error, instead of leaving build() to report a
+// missing field.
+//
+//     impl ServerBuilder {
+//         pub fn port(&mut self, port: u16) -> &mut Self { ... }
+//
+//         pub fn try_port<__Value>(
+//             &mut self,
+//             port: __Value,
+//         ) -> Result<&mut Self, <__Value as TryInto<u16>>::Error>
+//         where
+//             __Value: TryInto<u16>,
+//         { ... }
+//     }
+//
+// The try setter returns whatever the setter returns, so it also works with
+// the owned pattern and on typestate builders.
+
+use derive_builder::Builder;
+use std::convert::TryFrom;
+use std::num::TryFromIntError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Host(String);
+
+impl TryFrom<&str> for Host {
+    type Error = String;
+
+    fn try_from(host: &str) -> Result<Self, String> {
+        if host.is_empty() {
+            Err("empty host".to_owned())
+        } else {
+            Ok(Host(host.to_owned()))
+        }
+    }
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Server {
+    #[builder(try_setter)]
+    host: Host,
+    #[builder(try_setter)]
+    port: u16,
+    #[builder(try_setter, setter(name = "workers"))]
+    threads: Option<u8>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(typestate)]
+pub struct Limits {
+    #[builder(try_setter)]
+    max: u8,
+}
+
+fn main() -> Result<(), TryFromIntError> {
+    let server = Server::builder()
+        .try_host("localhost")
+        .unwrap()
+        .try_port(8080_u64)?
+        .try_workers(4_i32)?
+        .build()
+        .unwrap();
+    assert_eq!(
+        server,
+        Server {
+            host: Host("localhost".to_owned()),
+            port: 8080,
+            threads: Some(4),
+        }
+    );
+
+    let mut builder = Server::builder();
+    assert_eq!(builder.try_host("").err().unwrap(), "empty host");
+    assert!(builder.try_port(-1_i64).is_err());
+    assert!(builder.try_port(70000_u32).is_err());
+
+    let limits = Limits::builder().try_max(200_u32)?.build();
+    assert_eq!(limits, Limits { max: 200 });
+    assert!(Limits::builder().try_max(300_u32).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+// merge() layers one builder on top of another: every field the other builder
+// has a value for replaces the current one, and repeated fields get the other
+// builder's elements appended. Missing fields are still only reported by
+// build(), once every layer is merged.
+//
+//     impl CommandBuilder {
+//         pub fn merge(&mut self, other: CommandBuilder) -> &mut Self { ... }
+//     }
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let mut defaults = Command::builder();
+    defaults.executable("cargo".to_owned()).arg("build".to_owned());
+
+    let mut overrides = Command::builder();
+    overrides
+        .current_dir("..".to_owned())
+        .arg("--release".to_owned());
+
+    let command = defaults.merge(overrides).build().unwrap();
+    assert_eq!(
+        command,
+        Command {
+            executable: "cargo".to_owned(),
+            args: vec!["build".to_owned(), "--release".to_owned()],
+            current_dir: Some("..".to_owned()),
+        }
+    );
+
+    let mut overrides = Command::builder();
+    overrides.executable("rustc".to_owned());
+    let mut base = Command::builder();
+    base.current_dir("/".to_owned());
+    let command = base.merge(overrides).build().unwrap();
+    assert_eq!(command.executable, "rustc");
+    assert_eq!(command.current_dir.as_deref(), Some("/"));
+
+    let err = Command::builder().merge(Command::builder()).build().unwrap_err();
+    assert_eq!(err.to_string(), "missing: executable");
+}
@@ -0,0 +1,50 @@
+// With the `serde` feature of derive_builder enabled, builders derive
+// serde::Deserialize, so a configuration file can be read straight into a
+// builder. Every field is optional in the input; build() reports the ones that
+// are still missing after all layers are merged.
+//
+// Typestate builders do not derive Deserialize, since the input decides which
+// fields are set and the type could not know.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(default)]
+    env: Vec<String>,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Wrapper<T> {
+    inner: T,
+}
+
+fn main() {
+    let file: CommandBuilder = serde_json::from_str(
+        r#"{ "executable": "cargo", "args": ["build"], "current_dir": "/src" }"#,
+    )
+    .unwrap();
+    let cli: CommandBuilder = serde_json::from_str(r#"{ "args": ["--release"] }"#).unwrap();
+
+    let mut builder = Command::builder();
+    builder.merge(file).merge(cli);
+    assert_eq!(
+        builder.build().unwrap(),
+        Command {
+            executable: "cargo".to_owned(),
+            args: vec!["build".to_owned(), "--release".to_owned()],
+            env: Vec::new(),
+            current_dir: Some("/src".to_owned()),
+        }
+    );
+
+    let mut empty: CommandBuilder = serde_json::from_str("{}").unwrap();
+    assert_eq!(empty.build().unwrap_err().to_string(), "missing: executable");
+
+    let mut wrapper: WrapperBuilder<u8> = serde_json::from_str(r#"{ "inner": 7 }"#).unwrap();
+    assert_eq!(wrapper.build().unwrap(), Wrapper { inner: 7 });
+}
@@ -0,0 +1,96 @@
+// Like 09-redefined-prelude-types.rs, but for the rest of what the generated
+// code relies on: the macro must keep working when Vec, String, Default, Into
+// and friends mean something else at the call site, when the caller has local
+// modules named `core` or `std`, when a derive macro is imported as `Clone`,
+// and even without the prelude at all. Every path in the expansion is
+// absolute, `::core::...` where possible and `::std::...` for Vec and String.
+
+#![allow(dead_code, non_camel_case_types)]
+
+mod core {}
+mod std {}
+
+mod shadowed {
+    use derive_builder::Builder;
+
+    type Vec = ();
+    type String = ();
+    type Option = ();
+    type Some = ();
+    type None = ();
+    type Result = ();
+    type Ok = ();
+    type Err = ();
+    trait Default {}
+    trait Into {}
+    trait TryInto {}
+    trait Extend {}
+    trait Clone {}
+    trait Iterator {}
+
+    #[derive(Builder)]
+    #[builder(missing = "all", validate = "check", into)]
+    pub struct Command {
+        pub executable: ::std::string::String,
+        #[builder(each = "arg")]
+        pub args: ::std::vec::Vec<::std::string::String>,
+        #[builder(default, try_setter)]
+        pub retries: u8,
+        pub current_dir: ::std::option::Option<::std::string::String>,
+    }
+
+    #[derive(Builder)]
+    #[builder(typestate)]
+    pub enum Shape {
+        Circle { radius: u32 },
+        Rect { width: u32, height: u32 },
+    }
+
+    fn check(_: &Command) -> ::std::result::Result<(), ::std::string::String> {
+        ::std::result::Result::Ok(())
+    }
+}
+
+mod shadowed_derive {
+    use derive_builder::Builder;
+    use serde::Serialize as Clone;
+
+    #[derive(Builder)]
+    #[builder(pattern = "immutable")]
+    pub struct Timeout {
+        pub secs: u64,
+    }
+}
+
+mod without_prelude {
+    #![no_implicit_prelude]
+
+    use ::derive_builder::Builder;
+
+    #[derive(Builder)]
+    #[builder(pattern = "immutable")]
+    pub struct Point<T> {
+        pub x: T,
+        pub y: ::std::option::Option<T>,
+    }
+}
+
+fn main() {
+    let command = shadowed::Command::builder()
+        .executable("cargo")
+        .arg("build")
+        .try_retries(3_u64)
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(command.args, ["build"]);
+    assert_eq!(command.retries, 3);
+
+    let timeout = shadowed_derive::Timeout::builder().secs(5).build().unwrap();
+    assert_eq!(timeout.secs, 5);
+
+    let point = without_prelude::Point::builder().x(1).build().unwrap();
+    assert_eq!(point.y, None);
+
+    let _ = shadowed::Shape::builder_circle().radius(1).build();
+}
@@ -0,0 +1,52 @@
+// #[builder(no_std)] makes the generated code usable from a `no_std` crate
+// with `alloc`: Vec and String come from `::alloc`, which the crate has to
+// declare with `extern crate alloc`, and the builder error implements
+// core::error::Error.
+//
+// `std` is only linked here under another name so that the test has a panic
+// handler and a main function; `::std` paths would not resolve.
+
+#![no_std]
+
+extern crate alloc;
+extern crate std as _std;
+
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(no_std, missing = "all", validate = "check")]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn check(command: &Command) -> Result<(), String> {
+    if command.executable.is_empty() {
+        Err("empty executable".to_owned())
+    } else {
+        Ok(())
+    }
+}
+
+fn assert_error<E: core::error::Error>(_: &E) {}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(command.args, ["build"]);
+
+    let err = Command::builder().build().unwrap_err();
+    assert_error(&err);
+    assert_eq!(err.to_string(), "missing: executable");
+
+    let err = Command::builder().executable(String::new()).build().unwrap_err();
+    assert_eq!(err.to_string(), "empty executable");
+}
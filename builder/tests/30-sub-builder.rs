@@ -0,0 +1,160 @@
+// A field whose type also derives Builder can be marked
+// #[builder(sub_builder)]. The outer builder then holds the field's builder,
+// `SandboxBuilder` for a `Sandbox`, and hands it out to be edited in place:
+//
+//     impl CommandBuilder {
+//         pub fn sandbox(&mut self) -> &mut SandboxBuilder { ... }
+//     }
+//
+// The outer build() builds the nested builder too. Its errors are wrapped in
+// an `InvalidSandbox` variant whose message names the path of fields leading
+// to the problem, such as "missing: sandbox.allow_net". Nested builders are
+// built in place, each checking its own fields before taking any out, and
+// before anything else is taken out of the outer builder, so after such an
+// error the builder can be fixed and built again whatever pattern the nested
+// builder uses.
+//
+// Every builder that is not a typestate builder implements Default, which is
+// how the nested builder starts out. The nested builder may use any pattern,
+// owned and immutable included, but cannot be a typestate builder: its type
+// changes with every setter, so there is no one type for the field to hold.
+
+use derive_builder::Builder;
+
+mod sandbox {
+    use derive_builder::Builder;
+
+    #[derive(Builder, Clone, Debug, PartialEq)]
+    #[builder(validate = "check_limits")]
+    pub struct Limits {
+        pub memory: u64,
+        pub cpus: u32,
+    }
+
+    fn check_limits(limits: &Limits) -> Result<(), String> {
+        if limits.cpus == 0 {
+            return Err("cpus must be positive".to_owned());
+        }
+        Ok(())
+    }
+
+    #[derive(Builder, Clone, Debug, PartialEq)]
+    pub struct Sandbox {
+        pub allow_net: bool,
+        #[builder(sub_builder)]
+        pub limits: Limits,
+    }
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+pub struct Command {
+    executable: String,
+    #[builder(sub_builder)]
+    sandbox: sandbox::Sandbox,
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(pattern = "owned")]
+pub struct Retry {
+    attempts: u32,
+    delay: u32,
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(pattern = "immutable")]
+pub struct Timeout {
+    secs: u64,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Job {
+    #[builder(sub_builder)]
+    retry: Retry,
+    #[builder(sub_builder)]
+    timeout: Timeout,
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    builder.executable("cargo".to_owned());
+    builder.sandbox().allow_net(true);
+    builder.sandbox().limits().memory(1 << 30).cpus(4);
+    let command = builder.build().unwrap();
+    assert_eq!(
+        command,
+        Command {
+            executable: "cargo".to_owned(),
+            sandbox: sandbox::Sandbox {
+                allow_net: true,
+                limits: sandbox::Limits {
+                    memory: 1 << 30,
+                    cpus: 4,
+                },
+            },
+        }
+    );
+
+    let mut builder = Command::builder();
+    builder.executable("cargo".to_owned());
+    let err = builder.build().unwrap_err();
+    assert!(matches!(
+        err,
+        CommandBuilderError::InvalidSandbox(sandbox::SandboxBuilderError::MissingAllowNet)
+    ));
+    assert_eq!(err.to_string(), "missing: sandbox.allow_net");
+    builder.sandbox().allow_net(true).limits().memory(1).cpus(1);
+    assert_eq!(builder.build().unwrap().executable, "cargo");
+
+    let mut builder = Command::builder();
+    builder.executable("cargo".to_owned());
+    builder.sandbox().allow_net(false).limits().memory(1);
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "missing: sandbox.limits.cpus");
+
+    let mut builder = Command::builder();
+    builder.executable("cargo".to_owned());
+    builder.sandbox().allow_net(false).limits().memory(1).cpus(0);
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "sandbox.limits: cpus must be positive");
+
+    let mut copy = command.to_builder();
+    copy.sandbox().limits().cpus(8);
+    assert_eq!(copy.build().unwrap().sandbox.limits.cpus, 8);
+
+    let mut base = CommandBuilder::default();
+    let mut overrides = Command::builder();
+    overrides.sandbox().allow_net(false);
+    base.merge(command.to_builder()).merge(overrides);
+    assert!(!base.build().unwrap().sandbox.allow_net);
+
+    // Owned and immutable builders are replaced rather than edited in place.
+    let mut builder = Job::builder();
+    *builder.retry() = Retry::builder().attempts(3).delay(1);
+    *builder.timeout() = builder.timeout().secs(30);
+    let err = Job::builder().build().unwrap_err();
+    assert_eq!(err.to_string(), "missing: retry.attempts");
+
+    let mut partial = Job::builder();
+    *partial.retry() = Retry::builder().attempts(5);
+    let err = partial.build().unwrap_err();
+    assert_eq!(err.to_string(), "missing: retry.delay");
+    *partial.retry() = std::mem::take(partial.retry()).delay(2);
+    let err = partial.build().unwrap_err();
+    assert_eq!(err.to_string(), "missing: timeout.secs");
+    *partial.timeout() = Timeout::builder().secs(1);
+    assert_eq!(partial.build().unwrap().retry.attempts, 5);
+
+    let mut overrides = Job::builder();
+    *overrides.timeout() = Timeout::builder().secs(60);
+    builder.merge(overrides);
+    assert_eq!(
+        builder.build().unwrap(),
+        Job {
+            retry: Retry {
+                attempts: 3,
+                delay: 1,
+            },
+            timeout: Timeout { secs: 60 },
+        }
+    );
+}
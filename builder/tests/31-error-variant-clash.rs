@@ -0,0 +1,23 @@
+// The variants of the builder error are named after fields, so two fields can
+// ask for the same one. With #[builder(missing = "all")] a required field
+// named `fields` would need `MissingFields`, which already lists every unset
+// field, and `max_len` and `max__len` would both need `MissingMaxLen`. The
+// derive reports the clash on the field instead of generating an enum that
+// does not compile.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(missing = "all")]
+pub struct Query {
+    fields: String,
+    other: u8,
+}
+
+#[derive(Builder)]
+pub struct Limits {
+    max_len: usize,
+    max__len: usize,
+}
+
+fn main() {}
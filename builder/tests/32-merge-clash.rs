@@ -0,0 +1,21 @@
+// Every builder that is not a typestate one has a merge() method, so a field
+// named `merge` cannot have a setter of that name. The derive reports the clash
+// on the field and points at `setter(name = "...")`, which sets the setter
+// apart from the field it fills.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Policy {
+    merge: bool,
+    #[builder(each = "merge")]
+    rules: Vec<String>,
+}
+
+#[derive(Builder)]
+pub struct Renamed {
+    #[builder(setter(name = "merge_with"))]
+    merge: bool,
+}
+
+fn main() {}
@@ -1,13 +1,38 @@
 #[test]
 fn tests() {
     let t = trybuild::TestCases::new();
-    //t.pass("tests/01-parse.rs");
-    //t.pass("tests/02-create-builder.rs");
-    //t.pass("tests/03-call-setters.rs");
-    //t.pass("tests/04-call-build.rs");
-    //t.pass("tests/05-method-chaining.rs");
-    //t.pass("tests/06-optional-field.rs");
-    //t.pass("tests/07-repeated-field.rs");
-    //t.compile_fail("tests/08-unrecognized-attribute.rs");
-    //t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/01-parse.rs");
+    t.pass("tests/02-create-builder.rs");
+    t.pass("tests/03-call-setters.rs");
+    t.pass("tests/04-call-build.rs");
+    t.pass("tests/05-method-chaining.rs");
+    t.pass("tests/06-optional-field.rs");
+    t.pass("tests/07-repeated-field.rs");
+    t.compile_fail("tests/08-unrecognized-attribute.rs");
+    t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-enum.rs");
+    t.pass("tests/11-default-field.rs");
+    t.compile_fail("tests/12-default-conflict.rs");
+    t.pass("tests/13-build-error.rs");
+    t.pass("tests/14-validate.rs");
+    t.pass("tests/15-generics.rs");
+    t.pass("tests/16-into-setters.rs");
+    t.pass("tests/17-pattern.rs");
+    t.pass("tests/18-each-collections.rs");
+    t.pass("tests/19-typestate.rs");
+    t.compile_fail("tests/20-typestate-missing.rs");
+    t.pass("tests/21-to-builder.rs");
+    t.compile_fail("tests/22-attribute-suggestions.rs");
+    t.pass("tests/23-tuple-structs.rs");
+    t.pass("tests/24-setter-options.rs");
+    t.pass("tests/25-try-setter.rs");
+    t.pass("tests/26-merge.rs");
+    t.pass("tests/28-hygiene.rs");
+    t.pass("tests/29-no-std.rs");
+    t.pass("tests/30-sub-builder.rs");
+    t.compile_fail("tests/31-error-variant-clash.rs");
+    t.compile_fail("tests/32-merge-clash.rs");
+
+    #[cfg(feature = "serde")]
+    t.pass("tests/27-deserialize.rs");
 }
@@ -0,0 +1,264 @@
+use crate::attr::{self, ContainerAttrs, Derive, Errors, FieldAttrs, VariantAttrs};
+use crate::bound;
+use proc_macro2::Span;
+use quote::format_ident;
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Error, Fields, Generics, Ident, Member, Result, Type};
+
+/// The derive input, reduced to the shapes that get printed.
+pub struct Input<'a> {
+    pub ident: &'a Ident,
+    pub generics: &'a Generics,
+    pub attrs: ContainerAttrs,
+    /// Whether the input is an enum; a struct has a single variant named
+    /// after itself.
+    pub is_enum: bool,
+    pub variants: Vec<Variant<'a>>,
+}
+
+pub struct Variant<'a> {
+    pub ident: &'a Ident,
+    /// For the variant standing in for a struct, only the `name` given to
+    /// the struct.
+    pub attrs: VariantAttrs,
+    pub style: Style,
+    pub fields: Vec<Field<'a>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Style {
+    /// `Struct { a: A }`, printed with `debug_struct`.
+    Named,
+    /// `Tuple(A)`, printed with `debug_tuple`.
+    Tuple,
+    /// `Unit`, printed as its bare name.
+    Unit,
+}
+
+pub struct Field<'a> {
+    /// How the field is reached on the struct or variant.
+    pub member: Member,
+    /// The local the field is bound to while printing.
+    pub binding: Ident,
+    pub ty: &'a Type,
+    pub attrs: FieldAttrs,
+}
+
+impl<'a> Input<'a> {
+    pub fn from_syn(node: &'a DeriveInput, derive: Derive) -> Result<Self> {
+        let mut errors = Errors::default();
+        let attrs = attr::container_attrs(&node.attrs, derive).unwrap_or_else(|error| {
+            errors.push(error);
+            ContainerAttrs::default()
+        });
+
+        if let (Data::Enum(_), Some(name)) = (&node.data, &attrs.name) {
+            errors.push(Error::new(
+                name.span(),
+                "an enum's own name is never printed; give `name` to its variants instead",
+            ));
+        }
+
+        let (is_enum, variants) = match &node.data {
+            Data::Struct(data) => (
+                false,
+                vec![Variant::from_syn(
+                    &node.ident,
+                    VariantAttrs {
+                        format: None,
+                        name: attrs.name.clone(),
+                    },
+                    &data.fields,
+                    derive,
+                    &mut errors,
+                )],
+            ),
+            Data::Enum(data) => (
+                true,
+                data.variants
+                    .iter()
+                    .map(|variant| {
+                        let attrs =
+                            attr::variant_attrs(&variant.attrs, derive).unwrap_or_else(|error| {
+                                errors.push(error);
+                                VariantAttrs::default()
+                            });
+                        Variant::from_syn(
+                            &variant.ident,
+                            attrs,
+                            &variant.fields,
+                            derive,
+                            &mut errors,
+                        )
+                    })
+                    .collect(),
+            ),
+            Data::Union(data) => {
+                return Err(Error::new_spanned(
+                    data.union_token,
+                    match derive {
+                        Derive::Debug => "CustomDebug does not support unions",
+                        Derive::Display => "CustomDisplay does not support unions",
+                    },
+                ))
+            }
+        };
+
+        let input = Input {
+            ident: &node.ident,
+            generics: &node.generics,
+            attrs,
+            is_enum,
+            variants,
+        };
+        if let Some(span) = input.attrs.transparent {
+            if let Err(error) = input.check_transparent(span) {
+                errors.push(error);
+            }
+        }
+        errors.finish(input)
+    }
+
+    /// The field a `transparent` struct is printed as.
+    pub fn transparent_field(&self) -> Option<&Field<'a>> {
+        self.attrs.transparent?;
+        self.fields().find(|field| !field.is_phantom_data())
+    }
+
+    fn check_transparent(&self, span: Span) -> Result<()> {
+        if self.is_enum {
+            return Err(Error::new(
+                span,
+                "`transparent` applies to structs, whose only field it prints",
+            ));
+        }
+        let others = [
+            ("`name`", self.attrs.name.is_some()),
+            ("`style`", self.attrs.style.is_some()),
+        ];
+        if let Some((other, _)) = others.iter().find(|(_, given)| *given) {
+            return Err(Error::new(
+                span,
+                format_args!("`transparent` cannot be combined with {}", other),
+            ));
+        }
+        let fields: Vec<&Field> = self
+            .fields()
+            .filter(|field| !field.is_phantom_data())
+            .collect();
+        match fields[..] {
+            [field] => match field.attrs.skip {
+                Some(skip) => Err(Error::new(
+                    skip,
+                    "the field of a `transparent` struct is all it prints, so it cannot be skipped",
+                )),
+                None => Ok(()),
+            },
+            _ => Err(Error::new(
+                span,
+                format_args!(
+                    "`transparent` needs exactly one field besides `PhantomData` markers, found {}",
+                    fields.len(),
+                ),
+            )),
+        }
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = &Field<'a>> {
+        self.variants.iter().flat_map(|variant| &variant.fields)
+    }
+}
+
+impl<'a> Variant<'a> {
+    /// The name the variant, or struct, is printed with.
+    pub fn name(&self) -> String {
+        match &self.attrs.name {
+            Some(name) => name.value(),
+            None => self.ident.unraw().to_string(),
+        }
+    }
+
+    /// Whether a field is left out of the output.
+    pub fn has_skipped(&self) -> bool {
+        self.fields.iter().any(|field| field.attrs.skip.is_some())
+    }
+
+    fn from_syn(
+        ident: &'a Ident,
+        attrs: VariantAttrs,
+        fields: &'a Fields,
+        derive: Derive,
+        errors: &mut Errors,
+    ) -> Self {
+        let style = match fields {
+            Fields::Named(_) => Style::Named,
+            Fields::Unnamed(_) => Style::Tuple,
+            Fields::Unit => Style::Unit,
+        };
+        let fields = fields
+            .iter()
+            .enumerate()
+            .filter_map(|(index, field)| {
+                Field::from_syn(field, index, derive)
+                    .map_err(|error| errors.push(error))
+                    .ok()
+            })
+            .collect();
+        Variant {
+            ident,
+            attrs,
+            style,
+            fields,
+        }
+    }
+}
+
+impl<'a> Field<'a> {
+    /// Whether the field's value goes through a `Debug` or format impl of
+    /// its type, which may need a bound. Skipped and redacted fields are not
+    /// printed, and a `with` function does its own printing.
+    pub fn uses_fmt_traits(&self) -> bool {
+        self.attrs.skip.is_none() && self.attrs.redact.is_none() && self.attrs.with.is_none()
+    }
+
+    /// Whether the field is a `PhantomData` marker, which a `transparent`
+    /// struct does not print.
+    pub fn is_phantom_data(&self) -> bool {
+        match self.ty {
+            Type::Path(ty) => ty.qself.is_none() && bound::is_phantom_data(&ty.path),
+            _ => false,
+        }
+    }
+
+    /// The name a named field is printed with.
+    pub fn name(&self) -> Option<String> {
+        match (&self.attrs.rename, &self.member) {
+            (Some(rename), _) => Some(rename.value()),
+            (None, Member::Named(ident)) => Some(ident.unraw().to_string()),
+            (None, Member::Unnamed(_)) => None,
+        }
+    }
+
+    fn from_syn(node: &'a syn::Field, index: usize, derive: Derive) -> Result<Self> {
+        let attrs = attr::field_attrs(&node.attrs, derive)?;
+        if let (Some(rename), None) = (&attrs.rename, &node.ident) {
+            return Err(Error::new(
+                rename.span(),
+                "a tuple field is printed without a name, so there is none to rename",
+            ));
+        }
+        let member = match &node.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(index.into()),
+        };
+        // Not nameable from the caller's format strings, so a field called
+        // `formatter` cannot clash with the generated code.
+        let binding = format_ident!("__field{}", index, span = Span::mixed_site());
+        Ok(Field {
+            member,
+            binding,
+            ty: &node.ty,
+            attrs,
+        })
+    }
+}
@@ -0,0 +1,504 @@
+use proc_macro2::{Span, TokenTree};
+use quote::quote;
+use syn::meta::ParseNestedMeta;
+use syn::punctuated::Punctuated;
+use syn::{
+    Attribute, Error, Expr, ExprLit, Lit, LitStr, Meta, MetaList, Path, Result, Token,
+    WherePredicate,
+};
+
+/// Which derive the attributes are read for. Each reads only its own
+/// attribute, so a type deriving both can configure them separately.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Derive {
+    /// `CustomDebug`, configured with `#[debug(...)]`.
+    Debug,
+    /// `CustomDisplay`, configured with `#[display(...)]`.
+    Display,
+}
+
+impl Derive {
+    fn attr(self) -> &'static str {
+        match self {
+            Derive::Debug => "debug",
+            Derive::Display => "display",
+        }
+    }
+}
+
+/// Options given to the struct or enum through `#[debug(...)]` or
+/// `#[display(...)]`.
+#[derive(Default)]
+pub struct ContainerAttrs {
+    /// `#[display("...")]`, the template a struct is displayed with.
+    pub format: Option<LitStr>,
+    /// `bound = "..."`, the where-predicates of the impl in place of the
+    /// inferred ones.
+    pub bound: Option<Vec<WherePredicate>>,
+    /// `bound(infer = "...")`, how bounds are inferred. `None` for the
+    /// default, which bounds the type parameters that fields print.
+    pub infer: Option<Infer>,
+    /// `name = "..."`, the name a struct is printed with in place of its
+    /// own.
+    pub name: Option<LitStr>,
+    /// `style = "..."`, the shape of the output when not the one
+    /// `#[derive(Debug)]` would give.
+    pub style: Option<Layout>,
+    /// `transparent`: a struct with one field is printed as that field.
+    pub transparent: Option<Span>,
+}
+
+#[derive(Clone, Copy)]
+pub enum Infer {
+    /// `"none"`: only the bounds written on the type and its fields.
+    None,
+    /// `"params"`: `T: Debug`, or `T: Display`, for every type parameter.
+    Params,
+    /// `"fields"`: `Type: Debug` for the type of every printed field, or
+    /// the trait each placeholder of a display template needs.
+    Fields,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Layout {
+    /// `"compact"`: on one line even under `{:#?}`, with no spaces around
+    /// the derive's own separators, as in `Field{name:"F",bitmask:28}`.
+    /// Field values keep their own `{:?}` output, spaces included.
+    Compact,
+    /// `"tuple"`: named fields are printed like a tuple struct's, without
+    /// their names.
+    Tuple,
+}
+
+/// Options given to an enum variant.
+#[derive(Default)]
+pub struct VariantAttrs {
+    /// `#[display("...")]`, the template the variant is displayed with.
+    pub format: Option<LitStr>,
+    /// `name = "..."`, the name the variant is printed with in place of its
+    /// own.
+    pub name: Option<LitStr>,
+}
+
+/// Options given to a single field.
+#[derive(Default)]
+pub struct FieldAttrs {
+    /// `#[debug = "..."]`, the format string the field is printed with.
+    pub format: Option<LitStr>,
+    /// `skip`: the field is left out, and the output ends with `..`.
+    pub skip: Option<Span>,
+    /// `redact` or `redact = "len"`: the field's value is hidden.
+    pub redact: Option<Redact>,
+    /// `with = "path::to::fn"`, a `fn(&T, &mut Formatter) -> fmt::Result`
+    /// printing the field in place of its `Debug` impl.
+    pub with: Option<Path>,
+    /// `bound = "..."`, the where-predicates needed to print this field in
+    /// place of the ones inferred from its type.
+    pub bound: Option<Vec<WherePredicate>>,
+    /// `rename = "..."`, the name the field is printed with in place of its
+    /// own.
+    pub rename: Option<LitStr>,
+}
+
+#[derive(Clone, Copy)]
+pub enum Redact {
+    /// Printed as `***`.
+    Mask,
+    /// Printed as `<redacted: 12>`, with the value's `len()`.
+    Len,
+}
+
+const CONTAINER_KEYS: &[&str] = &["bound", "name", "style", "transparent"];
+
+const DISPLAY_CONTAINER_KEYS: &[&str] = &["bound"];
+
+const VARIANT_KEYS: &[&str] = &["name"];
+
+const DISPLAY_VARIANT_KEYS: &[&str] = &[];
+
+const FIELD_KEYS: &[&str] = &["bound", "redact", "rename", "skip", "with"];
+
+const DISPLAY_FIELD_KEYS: &[&str] = &["bound"];
+
+pub fn container_attrs(attrs: &[Attribute], derive: Derive) -> Result<ContainerAttrs> {
+    let mut container = ContainerAttrs::default();
+    let mut errors = Errors::default();
+
+    let mut format = None;
+    let mut template = |lit| template(&mut format, lit);
+    let template: Option<&mut dyn FnMut(LitStr) -> Result<()>> = match derive {
+        Derive::Debug => None,
+        Derive::Display => Some(&mut template),
+    };
+    let keys = match derive {
+        Derive::Debug => CONTAINER_KEYS,
+        Derive::Display => DISPLAY_CONTAINER_KEYS,
+    };
+    let mut bound = None;
+    let mut infer = None;
+    let mut name = None;
+    let mut style = None;
+    let mut transparent = None;
+    parse_keys(attrs, derive, keys, &mut errors, template, |key, meta| {
+        match key {
+            "bound" if !meta.input.peek(Token![=]) => meta.parse_nested_meta(|meta| {
+                if !meta.path.is_ident("infer") {
+                    return Err(meta.error("expected `infer`"));
+                }
+                if infer.is_some() {
+                    return Err(meta.error("duplicate `infer` attribute"));
+                }
+                let span = meta.path.get_ident().unwrap().span();
+                let mode: LitStr = meta.value()?.parse()?;
+                let mode = match mode.value().as_str() {
+                    "none" => Infer::None,
+                    "params" => Infer::Params,
+                    "fields" => Infer::Fields,
+                    _ => {
+                        return Err(Error::new(
+                            mode.span(),
+                            "expected \"none\", \"params\" or \"fields\"",
+                        ))
+                    }
+                };
+                infer = Some((span, mode));
+                Ok(())
+            })?,
+            "bound" => {
+                if bound.is_some() {
+                    return Err(meta.error("duplicate `bound` attribute"));
+                }
+                bound = Some(where_predicates(meta)?);
+            }
+            "transparent" => {
+                if transparent.is_some() {
+                    return Err(meta.error("duplicate `transparent` attribute"));
+                }
+                transparent = Some(meta.path.get_ident().unwrap().span());
+            }
+            "name" => {
+                if name.is_some() {
+                    return Err(meta.error("duplicate `name` attribute"));
+                }
+                name = Some(meta.value()?.parse()?);
+            }
+            "style" => {
+                if style.is_some() {
+                    return Err(meta.error("duplicate `style` attribute"));
+                }
+                let name: LitStr = meta.value()?.parse()?;
+                style = Some(match name.value().as_str() {
+                    "compact" => Layout::Compact,
+                    "tuple" => Layout::Tuple,
+                    _ => return Err(Error::new(name.span(), "expected \"compact\" or \"tuple\"")),
+                });
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    });
+    container.format = format;
+    container.name = name;
+    container.style = style;
+    container.transparent = transparent;
+    container.bound = bound;
+    container.infer = infer.map(|(_, mode)| mode);
+
+    if let (Some(_), Some((span, _))) = (&container.bound, infer) {
+        errors.push(Error::new(
+            span,
+            "`bound = \"...\"` replaces every inferred bound, so `bound(infer = \"...\")` has no effect",
+        ));
+    }
+
+    errors.finish(container)
+}
+
+pub fn variant_attrs(attrs: &[Attribute], derive: Derive) -> Result<VariantAttrs> {
+    let mut errors = Errors::default();
+
+    let mut format = None;
+    let mut template = |lit| template(&mut format, lit);
+    let template: Option<&mut dyn FnMut(LitStr) -> Result<()>> = match derive {
+        Derive::Debug => None,
+        Derive::Display => Some(&mut template),
+    };
+    let keys = match derive {
+        Derive::Debug => VARIANT_KEYS,
+        Derive::Display => DISPLAY_VARIANT_KEYS,
+    };
+    let mut name = None;
+    parse_keys(attrs, derive, keys, &mut errors, template, |_, meta| {
+        if name.is_some() {
+            return Err(meta.error("duplicate `name` attribute"));
+        }
+        name = Some(meta.value()?.parse()?);
+        Ok(())
+    });
+    errors.finish(VariantAttrs { format, name })
+}
+
+/// Stores the display template of a struct or variant.
+fn template(slot: &mut Option<LitStr>, template: LitStr) -> Result<()> {
+    if slot.is_some() {
+        return Err(Error::new(template.span(), "duplicate display template"));
+    }
+    *slot = Some(template);
+    Ok(())
+}
+
+pub fn field_attrs(attrs: &[Attribute], derive: Derive) -> Result<FieldAttrs> {
+    let mut field = FieldAttrs::default();
+    let mut errors = Errors::default();
+
+    if derive == Derive::Display {
+        let mut bound = None;
+        parse_keys(
+            attrs,
+            derive,
+            DISPLAY_FIELD_KEYS,
+            &mut errors,
+            None,
+            |_, meta| {
+                if bound.is_some() {
+                    return Err(meta.error("duplicate `bound` attribute"));
+                }
+                bound = Some(where_predicates(meta)?);
+                Ok(())
+            },
+        );
+        field.bound = bound;
+        return errors.finish(field);
+    }
+
+    let mut format = |format: LitStr| {
+        if field.format.is_some() {
+            return Err(Error::new(format.span(), "duplicate format string"));
+        }
+        field.format = Some(format);
+        Ok(())
+    };
+    let mut skip = None;
+    let mut redact = None;
+    let mut with = None;
+    let mut bound = None;
+    let mut rename = None;
+    parse_keys(
+        attrs,
+        derive,
+        FIELD_KEYS,
+        &mut errors,
+        Some(&mut format),
+        |key, meta| {
+            let span = meta.path.get_ident().unwrap().span();
+            match key {
+                "skip" => {
+                    if skip.is_some() {
+                        return Err(meta.error("duplicate `skip` attribute"));
+                    }
+                    skip = Some(span);
+                }
+                "redact" => {
+                    if redact.is_some() {
+                        return Err(meta.error("duplicate `redact` attribute"));
+                    }
+                    let mode = if meta.input.peek(Token![=]) {
+                        let mode: LitStr = meta.value()?.parse()?;
+                        match mode.value().as_str() {
+                            "len" => Redact::Len,
+                            _ => return Err(Error::new(mode.span(), "expected \"len\"")),
+                        }
+                    } else {
+                        Redact::Mask
+                    };
+                    redact = Some((span, mode));
+                }
+                "bound" => {
+                    if bound.is_some() {
+                        return Err(meta.error("duplicate `bound` attribute"));
+                    }
+                    bound = Some(where_predicates(meta)?);
+                }
+                "rename" => {
+                    if rename.is_some() {
+                        return Err(meta.error("duplicate `rename` attribute"));
+                    }
+                    rename = Some(meta.value()?.parse()?);
+                }
+                "with" => {
+                    if with.is_some() {
+                        return Err(meta.error("duplicate `with` attribute"));
+                    }
+                    let path: LitStr = meta.value()?.parse()?;
+                    with = Some((span, path.parse::<Path>()?));
+                }
+                _ => unreachable!(),
+            }
+            Ok(())
+        },
+    );
+
+    // Each of these decides on its own how the field is shown.
+    let ways = [
+        ("a format string", field.format.as_ref().map(LitStr::span)),
+        ("`skip`", skip),
+        ("`redact`", redact.map(|(span, _)| span)),
+        ("`with`", with.as_ref().map(|(span, _)| *span)),
+    ];
+    let given: Vec<_> = ways.iter().filter(|(_, span)| span.is_some()).collect();
+    if let [(first, _), (second, Some(span)), ..] = given[..] {
+        errors.push(Error::new(
+            *span,
+            format_args!("{} cannot be combined with {}", second, first),
+        ));
+    }
+    field.skip = skip;
+    field.redact = redact.map(|(_, mode)| mode);
+    field.with = with.map(|(_, path)| path);
+    field.bound = bound;
+    field.rename = rename;
+
+    errors.finish(field)
+}
+
+/// Calls `parse` with every key of every `#[debug(...)]` attribute that is one
+/// of `keys`, and `format` with the string of every `#[debug = "..."]`, which
+/// only fields accept. For `CustomDisplay` the attribute is `#[display(...)]`
+/// and the string is the template in `#[display("...")]`, which belongs on
+/// structs and variants instead. Errors are collected rather than ending the
+/// parse, so every mistake on an item is reported at once.
+fn parse_keys(
+    attrs: &[Attribute],
+    derive: Derive,
+    keys: &[&str],
+    errors: &mut Errors,
+    mut format: Option<&mut dyn FnMut(LitStr) -> Result<()>>,
+    mut parse: impl FnMut(&str, &ParseNestedMeta) -> Result<()>,
+) {
+    let name = derive.attr();
+    for attr in attrs {
+        if !attr.path().is_ident(name) {
+            continue;
+        }
+
+        let result = match &attr.meta {
+            Meta::List(list) if derive == Derive::Display && is_template(list) => {
+                match (&mut format, attr.parse_args::<LitStr>()) {
+                    (Some(format), Ok(template)) => format(template),
+                    (None, Ok(template)) => Err(Error::new(
+                        template.span(),
+                        "a display template belongs on a struct or an enum variant",
+                    )),
+                    (_, Err(error)) => Err(error),
+                }
+            }
+            Meta::NameValue(meta) if derive == Derive::Debug => match (&mut format, &meta.value) {
+                (
+                    Some(format),
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(lit), ..
+                    }),
+                ) => format(lit.clone()),
+                (Some(_), value) => Err(Error::new_spanned(value, "expected a format string")),
+                (None, _) => Err(Error::new_spanned(
+                    attr,
+                    "a format string belongs on a field",
+                )),
+            },
+            Meta::List(_) => attr.parse_nested_meta(|meta| {
+                let path = &meta.path;
+                let key = quote!(#path).to_string().replace(' ', "");
+                let result = if keys.contains(&key.as_str()) {
+                    parse(&key, &meta)
+                } else {
+                    Err(unknown_key(&meta, derive, &key, keys))
+                };
+                if let Err(error) = result {
+                    errors.push(error);
+                    skip_value(&meta)?;
+                }
+                Ok(())
+            }),
+            Meta::NameValue(_) | Meta::Path(_) => Err(Error::new_spanned(
+                attr,
+                match derive {
+                    Derive::Debug => "expected `#[debug = \"...\"]` or `#[debug(...)]`",
+                    Derive::Display => "expected `#[display(\"...\")]` or `#[display(...)]`",
+                },
+            )),
+        };
+
+        if let Err(error) = result {
+            errors.push(error);
+        }
+    }
+}
+
+/// Whether `#[display(...)]` holds a template rather than keys.
+fn is_template(list: &MetaList) -> bool {
+    list.tokens
+        .clone()
+        .into_iter()
+        .next()
+        .is_some_and(|tt| syn::parse2::<LitStr>(tt.into()).is_ok())
+}
+
+fn unknown_key(meta: &ParseNestedMeta, derive: Derive, key: &str, keys: &[&str]) -> Error {
+    let name = derive.attr();
+    if keys.is_empty() {
+        return meta.error(format_args!("unknown {} attribute `{}`", name, key));
+    }
+    let expected = keys
+        .iter()
+        .map(|key| format!("`{}`", key))
+        .collect::<Vec<_>>()
+        .join(", ");
+    meta.error(format_args!(
+        "unknown {} attribute `{}`, expected one of {}",
+        name, key, expected,
+    ))
+}
+
+/// `"T::Value: Debug, U: Debug"`, parsed as where-predicates.
+fn where_predicates(meta: &ParseNestedMeta) -> Result<Vec<WherePredicate>> {
+    let lit: LitStr = meta.value()?.parse()?;
+    let predicates = lit.parse_with(Punctuated::<WherePredicate, Token![,]>::parse_terminated)?;
+    Ok(predicates.into_iter().collect())
+}
+
+/// Consumes whatever follows a key up to the next comma, so that parsing can
+/// go on with the next key after an error.
+fn skip_value(meta: &ParseNestedMeta) -> Result<()> {
+    meta.input.step(|cursor| {
+        let mut rest = *cursor;
+        while let Some((tt, next)) = rest.token_tree() {
+            match &tt {
+                TokenTree::Punct(punct) if punct.as_char() == ',' => break,
+                _ => rest = next,
+            }
+        }
+        Ok(((), rest))
+    })
+}
+
+/// Errors collected while parsing, reported together.
+#[derive(Default)]
+pub struct Errors {
+    combined: Option<Error>,
+}
+
+impl Errors {
+    pub fn push(&mut self, error: Error) {
+        match &mut self.combined {
+            Some(combined) => combined.combine(error),
+            None => self.combined = Some(error),
+        }
+    }
+
+    pub fn finish<T>(self, value: T) -> Result<T> {
+        match self.combined {
+            Some(error) => Err(error),
+            None => Ok(value),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! Inference of the where-clause of the `Debug` impl.
+//!
+//! Bounding every type parameter would reject `Field<NotDebug>` even when the
+//! parameter only appears in `PhantomData<T>`, and bounding every field type
+//! overflows on recursive types such as `One<T>` holding a `Box<Two<T>>`. So a
+//! parameter is bounded where it appears directly in a field type, except
+//! inside `PhantomData`, and associated types such as `T::Value` get a bound
+//! of their own instead of one on `T`. Fields that are skipped, redacted or
+//! printed by a `with` function add no bounds. `CustomDisplay` bounds only
+//! the fields its templates use, each with the trait its placeholder asks
+//! for, so `{bits:b}` adds `T: Binary`.
+//!
+//! `#[debug(bound(infer = "..."))]` picks another strategy when this one does
+//! not fit, and `#[debug(bound = "...")]` on a field stands in for whatever
+//! that field alone would have added.
+
+use crate::ast::{Field, Input};
+use crate::attr::Infer;
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::BTreeSet;
+use syn::visit::{self, Visit};
+use syn::{Ident, Path, TypePath};
+
+/// The inferred predicates for printing `printed`, each field with the trait
+/// it goes through. `fmt_trait` is the trait the derive implements, which
+/// `infer = "params"` bounds every type parameter with.
+pub fn where_predicates(
+    input: &Input,
+    printed: &[(&Field, TokenStream)],
+    fmt_trait: &TokenStream,
+) -> Vec<TokenStream> {
+    let mut predicates: Vec<TokenStream> = input
+        .fields()
+        .filter_map(|field| field.attrs.bound.as_ref())
+        .flatten()
+        .map(|predicate| quote!(#predicate))
+        .collect();
+    if let Some(bound) = &input.attrs.bound {
+        predicates.extend(bound.iter().map(|predicate| quote!(#predicate)));
+        return predicates;
+    }
+
+    // Fields with a bound of their own add nothing more.
+    let inferred = printed
+        .iter()
+        .filter(|(field, _)| field.attrs.bound.is_none());
+    let mut seen = BTreeSet::new();
+    let mut push = |predicate: TokenStream| {
+        if seen.insert(predicate.to_string()) {
+            predicates.push(predicate);
+        }
+    };
+    match input.attrs.infer {
+        Some(Infer::None) => {}
+        Some(Infer::Params) => {
+            for param in input.generics.type_params() {
+                let param = &param.ident;
+                push(quote!(#param: #fmt_trait));
+            }
+        }
+        Some(Infer::Fields) => {
+            for (field, fmt_trait) in inferred {
+                let ty = field.ty;
+                push(quote!(#ty: #fmt_trait));
+            }
+        }
+        None => {
+            let params: Vec<&Ident> = input
+                .generics
+                .type_params()
+                .map(|param| &param.ident)
+                .collect();
+            for (field, fmt_trait) in inferred {
+                let mut visitor = Params {
+                    params: &params,
+                    used: BTreeSet::new(),
+                    associated: Vec::new(),
+                };
+                visitor.visit_type(field.ty);
+                for param in params.iter().filter(|param| visitor.used.contains(*param)) {
+                    push(quote!(#param: #fmt_trait));
+                }
+                for path in visitor.associated {
+                    push(quote!(#path: #fmt_trait));
+                }
+            }
+        }
+    }
+    predicates
+}
+
+struct Params<'p> {
+    params: &'p [&'p Ident],
+    used: BTreeSet<&'p Ident>,
+    /// Paths such as `T::Value`, in the order they appear.
+    associated: Vec<TypePath>,
+}
+
+impl<'ast, 'p> Visit<'ast> for Params<'p> {
+    fn visit_type_path(&mut self, ty: &'ast TypePath) {
+        if ty.qself.is_none() {
+            if let Some(param) = self.param(&ty.path) {
+                if ty.path.segments.len() == 1 {
+                    self.used.insert(param);
+                } else {
+                    self.associated.push(ty.clone());
+                    return;
+                }
+            }
+        }
+        if is_phantom_data(&ty.path) {
+            return;
+        }
+        visit::visit_type_path(self, ty);
+    }
+}
+
+impl<'p> Params<'p> {
+    fn param(&self, path: &Path) -> Option<&'p Ident> {
+        if path.leading_colon.is_some() {
+            return None;
+        }
+        let first = &path.segments.first()?.ident;
+        self.params.iter().copied().find(|param| *param == first)
+    }
+}
+
+pub fn is_phantom_data(path: &Path) -> bool {
+    path.segments
+        .last()
+        .is_some_and(|segment| segment.ident == "PhantomData")
+}
@@ -0,0 +1,109 @@
+use crate::ast::{Input, Variant};
+use crate::attr::{Derive, Errors};
+use crate::bound;
+use crate::expand;
+use crate::template::{self, Template};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Error, LitStr, Result};
+
+pub fn derive(node: &DeriveInput) -> Result<TokenStream> {
+    let input = Input::from_syn(node, Derive::Display)?;
+    let ident = input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut errors = Errors::default();
+    if input.is_enum {
+        if let Some(format) = &input.attrs.format {
+            errors.push(Error::new(
+                format.span(),
+                "an enum is displayed by variant; give each variant its own `#[display(\"...\")]`",
+            ));
+        }
+    }
+    let mut templates = Vec::new();
+    for variant in &input.variants {
+        let format = if input.is_enum {
+            variant.attrs.format.as_ref()
+        } else {
+            input.attrs.format.as_ref()
+        };
+        let result = match format {
+            Some(format) => template::parse(format, variant),
+            None => Err(Error::new(
+                variant.ident.span(),
+                "missing `#[display(\"...\")]` template",
+            )),
+        };
+        match result {
+            Ok(template) => templates.push((variant, format.unwrap(), template)),
+            Err(error) => errors.push(error),
+        }
+    }
+    let templates = errors.finish(templates)?;
+
+    let mut predicates: Vec<TokenStream> = match where_clause {
+        Some(where_clause) => where_clause
+            .predicates
+            .iter()
+            .map(|predicate| quote!(#predicate))
+            .collect(),
+        None => Vec::new(),
+    };
+    let printed: Vec<_> = templates
+        .iter()
+        .flat_map(|(_, _, template)| template.printed.iter().cloned())
+        .collect();
+    let fmt_trait = quote!(::core::fmt::Display);
+    predicates.extend(bound::where_predicates(&input, &printed, &fmt_trait));
+
+    let formatter = expand::formatter();
+    let arms = templates
+        .iter()
+        .map(|(variant, format, template)| expand_variant(&input, variant, format, template));
+
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Display for #ident #ty_generics
+        where
+            #(#predicates,)*
+        {
+            fn fmt(&self, #formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                match *self {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
+/// The match arm displaying one variant, or the struct itself, with its
+/// template.
+fn expand_variant(
+    input: &Input,
+    variant: &Variant,
+    format: &LitStr,
+    template: &Template,
+) -> TokenStream {
+    let ident = input.ident;
+    let path = if input.is_enum {
+        let variant = variant.ident;
+        quote!(#ident::#variant)
+    } else {
+        quote!(#ident)
+    };
+    let formatter = expand::formatter();
+
+    let bindings = template.args.iter().map(|field| {
+        let member = &field.member;
+        let binding = &field.binding;
+        quote!(#member: ref #binding)
+    });
+    let format = LitStr::new(&template.format, format.span());
+    let args = template.args.iter().map(|field| &field.binding);
+
+    quote! {
+        #path { #(#bindings,)* .. } => {
+            ::core::fmt::Formatter::write_fmt(#formatter, ::core::format_args!(#format #(, #args)*))
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use crate::ast::{Field, Input, Style, Variant};
+use crate::attr::{Derive, Layout, Redact};
+use crate::bound;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{DeriveInput, Ident, Result};
+
+pub fn derive(node: &DeriveInput) -> Result<TokenStream> {
+    let input = Input::from_syn(node, Derive::Debug)?;
+    let ident = input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut predicates: Vec<TokenStream> = match where_clause {
+        Some(where_clause) => where_clause
+            .predicates
+            .iter()
+            .map(|predicate| quote!(#predicate))
+            .collect(),
+        None => Vec::new(),
+    };
+    let fmt_trait = quote!(::core::fmt::Debug);
+    let printed: Vec<_> = input
+        .fields()
+        .filter(|field| field.uses_fmt_traits())
+        .filter(|field| input.attrs.transparent.is_none() || !field.is_phantom_data())
+        .map(|field| (field, fmt_trait.clone()))
+        .collect();
+    predicates.extend(bound::where_predicates(&input, &printed, &fmt_trait));
+
+    let formatter = formatter();
+    let arms: Vec<TokenStream> = match input.transparent_field() {
+        Some(field) => vec![expand_transparent(&input, field)],
+        None => input
+            .variants
+            .iter()
+            .map(|variant| expand_variant(&input, variant))
+            .collect(),
+    };
+
+    // Items inside `fmt` cannot use the impl's generics, so the wrapper is
+    // generic on its own.
+    let with_wrapper = if input.fields().any(|field| field.attrs.with.is_some()) {
+        let wrapper = with_wrapper();
+        Some(quote! {
+            struct #wrapper<'__a, T: ?::core::marker::Sized>(
+                &'__a T,
+                fn(&T, &mut ::core::fmt::Formatter) -> ::core::fmt::Result,
+            );
+
+            impl<'__a, T: ?::core::marker::Sized> ::core::fmt::Debug for #wrapper<'__a, T> {
+                fn fmt(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    (self.1)(self.0, formatter)
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Debug for #ident #ty_generics
+        where
+            #(#predicates,)*
+        {
+            fn fmt(&self, #formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                #with_wrapper
+                match *self {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
+pub fn formatter() -> Ident {
+    Ident::new("formatter", Span::mixed_site())
+}
+
+/// Implements `Debug` by calling the function given with `with = "..."`.
+fn with_wrapper() -> Ident {
+    Ident::new("DebugWith", Span::mixed_site())
+}
+
+fn debug() -> Ident {
+    Ident::new("debug", Span::mixed_site())
+}
+
+/// The match arm printing one variant, or the struct itself.
+fn expand_variant(input: &Input, variant: &Variant) -> TokenStream {
+    let ident = input.ident;
+    let path = if input.is_enum {
+        let variant = variant.ident;
+        quote!(#ident::#variant)
+    } else {
+        quote!(#ident)
+    };
+    let name = variant.name();
+    let formatter = formatter();
+    let debug = debug();
+
+    let bindings = variant.fields.iter().map(|field| {
+        let member = &field.member;
+        let binding = &field.binding;
+        match (field.attrs.skip, field.attrs.redact) {
+            (Some(_), _) | (_, Some(Redact::Mask)) => quote!(#member: _),
+            _ => quote!(#member: ref #binding),
+        }
+    });
+    let pattern = quote!(#path { #(#bindings,)* });
+
+    // Ending with `..` shows that something was left out.
+    let finish = if variant.has_skipped() {
+        quote!(finish_non_exhaustive)
+    } else {
+        quote!(finish)
+    };
+
+    let body = match (input.attrs.style, variant.style) {
+        (_, Style::Unit) => quote!(::core::fmt::Formatter::write_str(#formatter, #name)),
+        (Some(Layout::Compact), style) => compact(variant, style, &name),
+        (None, Style::Named) => {
+            let fields = variant
+                .fields
+                .iter()
+                .filter(|field| field.attrs.skip.is_none());
+            let fields = fields.map(|field| {
+                let name = field.name().unwrap();
+                let value = value(field);
+                quote!(::core::fmt::DebugStruct::field(&mut #debug, #name, #value);)
+            });
+            quote! {
+                let mut #debug = ::core::fmt::Formatter::debug_struct(#formatter, #name);
+                #(#fields)*
+                ::core::fmt::DebugStruct::#finish(&mut #debug)
+            }
+        }
+        (Some(Layout::Tuple), _) | (None, Style::Tuple) => {
+            let fields = variant
+                .fields
+                .iter()
+                .filter(|field| field.attrs.skip.is_none());
+            let fields = fields.map(|field| {
+                let value = value(field);
+                quote!(::core::fmt::DebugTuple::field(&mut #debug, #value);)
+            });
+            quote! {
+                let mut #debug = ::core::fmt::Formatter::debug_tuple(#formatter, #name);
+                #(#fields)*
+                ::core::fmt::DebugTuple::#finish(&mut #debug)
+            }
+        }
+    };
+
+    quote! {
+        #pattern => {
+            #body
+        }
+    }
+}
+
+/// The match arm of a `transparent` struct, which hands the formatter with
+/// all its flags to the one field.
+fn expand_transparent(input: &Input, field: &Field) -> TokenStream {
+    let ident = input.ident;
+    let member = &field.member;
+    let binding = &field.binding;
+    let formatter = formatter();
+    let value = value(field);
+    let pattern = match field.attrs.redact {
+        Some(Redact::Mask) => quote!(#ident { .. }),
+        _ => quote!(#ident { #member: ref #binding, .. }),
+    };
+    quote! {
+        #pattern => ::core::fmt::Debug::fmt(#value, #formatter),
+    }
+}
+
+/// The variant written out with a single format string, which ignores the
+/// caller's `{:#?}` and puts no spaces around names, braces and commas:
+/// `Field{name:"F",bitmask:28}`. Each field is printed with a plain `{:?}`,
+/// so `inner: vec![1, 2]` still shows up as `inner:[1, 2]`.
+fn compact(variant: &Variant, style: Style, name: &str) -> TokenStream {
+    let formatter = formatter();
+    let mut parts = Vec::new();
+    let mut args = Vec::new();
+    for field in variant
+        .fields
+        .iter()
+        .filter(|field| field.attrs.skip.is_none())
+    {
+        parts.push(match field.name() {
+            Some(name) if style == Style::Named => format!("{}:{{:?}}", escape(&name)),
+            _ => "{:?}".to_owned(),
+        });
+        args.push(value(field));
+    }
+    if variant.has_skipped() {
+        parts.push("..".to_owned());
+    }
+
+    let mut format = escape(name);
+    if !parts.is_empty() {
+        let (open, close) = match style {
+            Style::Named => ("{{", "}}"),
+            Style::Tuple | Style::Unit => ("(", ")"),
+        };
+        format.push_str(open);
+        format.push_str(&parts.join(","));
+        format.push_str(close);
+    }
+    quote! {
+        ::core::fmt::Formatter::write_fmt(#formatter, ::core::format_args!(#format #(, #args)*))
+    }
+}
+
+/// A name as it is written in a format string.
+fn escape(name: &str) -> String {
+    name.replace('{', "{{").replace('}', "}}")
+}
+
+/// What a field is printed through: the field itself, the format string given
+/// with `#[debug = "..."]` applied to it, its `with` function, or a stand-in
+/// for a redacted value.
+fn value(field: &Field) -> TokenStream {
+    let binding = &field.binding;
+    if let Some(with) = &field.attrs.with {
+        let wrapper = with_wrapper();
+        let value = Ident::new("value", Span::mixed_site());
+        let formatter = formatter();
+        // Called from a closure so that the argument can deref-coerce, as
+        // when a `&Vec<u8>` is given to a function taking `&[u8]`.
+        return quote! {
+            &#wrapper(#binding, |#value, #formatter| #with(#value, #formatter))
+        };
+    }
+    if let Some(redact) = field.attrs.redact {
+        return match redact {
+            Redact::Mask => quote!(&::core::format_args!("***")),
+            Redact::Len => quote!(&::core::format_args!("<redacted: {}>", #binding.len())),
+        };
+    }
+    match &field.attrs.format {
+        Some(format) => quote!(&::core::format_args!(#format, #binding)),
+        None => quote!(#binding),
+    }
+}
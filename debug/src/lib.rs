@@ -1,8 +1,25 @@
+mod ast;
+mod attr;
+mod bound;
+mod display;
+mod expand;
+mod template;
+
 use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
 
-#[proc_macro_derive(CustomDebug)]
+#[proc_macro_derive(CustomDebug, attributes(debug))]
 pub fn derive(input: TokenStream) -> TokenStream {
-    let _ = input;
+    let input = parse_macro_input!(input as DeriveInput);
+    expand::derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    unimplemented!()
+#[proc_macro_derive(CustomDisplay, attributes(display))]
+pub fn derive_display(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    display::derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
@@ -0,0 +1,192 @@
+//! Parsing of `#[display("...")]` templates.
+//!
+//! A template is a format string whose arguments are fields: `{name}` and
+//! `{0}` name a field by name or position, `{}` takes the next field in
+//! order, and `name$` or `0$` in a format spec takes a width or precision
+//! from a field. The template is rewritten into a format string for
+//! `format_args!` with every field replaced by the position of its argument,
+//! so a field used twice is passed once and unused fields are not passed.
+
+use crate::ast::{Field, Variant};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Error, LitStr, Member, Result};
+
+pub struct Template<'a> {
+    /// The format string handed to `format_args!`.
+    pub format: String,
+    /// The arguments of the format string, in order.
+    pub args: Vec<&'a Field<'a>>,
+    /// Every field printed by a placeholder, with the formatting trait it
+    /// is printed through.
+    pub printed: Vec<(&'a Field<'a>, TokenStream)>,
+}
+
+pub fn parse<'a>(lit: &LitStr, variant: &'a Variant<'a>) -> Result<Template<'a>> {
+    let mut parser = Parser {
+        lit,
+        variant,
+        template: Template {
+            format: String::new(),
+            args: Vec::new(),
+            printed: Vec::new(),
+        },
+        next: 0,
+    };
+
+    let value = lit.value();
+    let mut rest = value.as_str();
+    while let Some(i) = rest.find(['{', '}']) {
+        parser.template.format.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            parser.template.format.push_str(&rest[..2]);
+            rest = &rest[2..];
+        } else if rest.starts_with('}') {
+            return Err(
+                parser.error("unmatched `}` in display template; write `}}` for a literal `}`")
+            );
+        } else {
+            let end = match rest.find('}') {
+                Some(end) => end,
+                None => {
+                    return Err(parser.error(
+                        "unterminated `{` in display template; write `{{` for a literal `{`",
+                    ))
+                }
+            };
+            parser.placeholder(&rest[1..end])?;
+            rest = &rest[end + 1..];
+        }
+    }
+    parser.template.format.push_str(rest);
+
+    Ok(parser.template)
+}
+
+struct Parser<'a, 'l> {
+    lit: &'l LitStr,
+    variant: &'a Variant<'a>,
+    template: Template<'a>,
+    /// The field `{}` takes next.
+    next: usize,
+}
+
+impl<'a, 'l> Parser<'a, 'l> {
+    /// Rewrites `{arg:spec}` with `arg` and any `name$` in `spec` replaced by
+    /// argument positions.
+    fn placeholder(&mut self, placeholder: &str) -> Result<()> {
+        let (arg, spec) = match placeholder.find(':') {
+            Some(colon) => (&placeholder[..colon], Some(&placeholder[colon + 1..])),
+            None => (placeholder, None),
+        };
+
+        let field = if arg.is_empty() {
+            self.next += 1;
+            self.field(&(self.next - 1).to_string())?
+        } else {
+            self.field(arg)?
+        };
+        let position = self.arg(field);
+        self.template.format.push('{');
+        self.template.format.push_str(&position.to_string());
+
+        let spec = match spec {
+            Some(spec) => spec,
+            None => {
+                self.template.format.push('}');
+                self.template.printed.push((field, fmt_trait("")));
+                return Ok(());
+            }
+        };
+        if spec.contains(".*") {
+            return Err(self.error(
+                "`.*` is not supported in display templates; take the precision from a field, as in `{:.precision$}`",
+            ));
+        }
+
+        // A count such as `width$` names a `usize` field, which needs no
+        // bound.
+        let mut rewritten = String::new();
+        let mut rest = spec;
+        while let Some(dollar) = rest.find('$') {
+            let mut start = rest[..dollar]
+                .rfind(|ch: char| !ch.is_alphanumeric() && ch != '_')
+                .map_or(0, |i| i + 1);
+            // In `0width$` and `01$` the `0` is the zero-padding flag, which
+            // a precision does not take.
+            let count = &rest[start..dollar];
+            if count.len() > 1 && count.starts_with('0') && !rest[..start].ends_with('.') {
+                start += 1;
+            }
+            let count = self.field(&rest[start..dollar])?;
+            let position = self.arg(count);
+            rewritten.push_str(&rest[..start]);
+            rewritten.push_str(&position.to_string());
+            rewritten.push('$');
+            rest = &rest[dollar + 1..];
+        }
+        rewritten.push_str(rest);
+
+        self.template.format.push(':');
+        self.template.format.push_str(&rewritten);
+        self.template.format.push('}');
+        self.template.printed.push((field, fmt_trait(spec)));
+        Ok(())
+    }
+
+    /// The field named `arg`, by name or by position.
+    fn field(&self, arg: &str) -> Result<&'a Field<'a>> {
+        let fields = &self.variant.fields;
+        let variant = self.variant.ident;
+        if let Ok(index) = arg.parse::<usize>() {
+            fields
+                .get(index)
+                .ok_or_else(|| self.error(format_args!("`{}` has no field {}", variant, index)))
+        } else {
+            let name = arg.strip_prefix("r#").unwrap_or(arg);
+            fields
+                .iter()
+                .find(|field| match &field.member {
+                    Member::Named(ident) => ident.unraw() == name,
+                    Member::Unnamed(_) => false,
+                })
+                .ok_or_else(|| {
+                    self.error(format_args!("`{}` has no field named `{}`", variant, arg))
+                })
+        }
+    }
+
+    /// The position of the argument passing `field`, added if new.
+    fn arg(&mut self, field: &'a Field<'a>) -> usize {
+        let args = &mut self.template.args;
+        match args.iter().position(|arg| arg.binding == field.binding) {
+            Some(position) => position,
+            None => {
+                args.push(field);
+                args.len() - 1
+            }
+        }
+    }
+
+    fn error(&self, message: impl std::fmt::Display) -> Error {
+        Error::new(self.lit.span(), message)
+    }
+}
+
+/// The trait a placeholder with this format spec prints through, from the
+/// type at its end: `?` for `Debug`, `x` for `LowerHex` and so on.
+fn fmt_trait(spec: &str) -> TokenStream {
+    let name = match spec.chars().last() {
+        Some('?') => quote!(Debug),
+        Some('x') => quote!(LowerHex),
+        Some('X') => quote!(UpperHex),
+        Some('o') => quote!(Octal),
+        Some('b') => quote!(Binary),
+        Some('e') => quote!(LowerExp),
+        Some('E') => quote!(UpperExp),
+        _ => quote!(Display),
+    };
+    quote!(::core::fmt::#name)
+}
@@ -0,0 +1,59 @@
+// Enums print the way std's derived Debug prints them: unit variants as their
+// bare name, tuple variants with `debug_tuple` and struct variants with
+// `debug_struct`, without the enum's name in front. A `#[debug = "..."]`
+// format works on the fields of any variant, named or positional, just as it
+// does on struct fields.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+#[derive(CustomDebug)]
+pub enum Event<T> {
+    Started,
+    Progress(u32, #[debug = "{}%"] u8),
+    Finished {
+        value: T,
+        #[debug = "0x{:04x}"]
+        code: u16,
+    },
+    Empty {},
+}
+
+#[derive(CustomDebug)]
+pub enum Never {}
+
+#[derive(CustomDebug)]
+pub struct Point(i32, #[debug = "{:+}"] i32);
+
+#[derive(CustomDebug)]
+pub struct Unit;
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_eq!(format!("{:?}", Event::<()>::Started), "Started");
+    assert_eq!(
+        format!("{:?}", Event::<()>::Progress(3, 50)),
+        "Progress(3, 50%)"
+    );
+    assert_eq!(
+        format!(
+            "{:?}",
+            Event::Finished {
+                value: "done",
+                code: 255,
+            }
+        ),
+        r#"Finished { value: "done", code: 0x00ff }"#
+    );
+    assert_eq!(format!("{:?}", Event::<()>::Empty {}), "Empty");
+    assert_eq!(
+        format!("{:#?}", Event::<u8>::Progress(1, 2)),
+        "Progress(\n    1,\n    2%,\n)"
+    );
+
+    assert_eq!(format!("{:?}", Point(1, 2)), "Point(1, +2)");
+    assert_eq!(format!("{:?}", Unit), "Unit");
+
+    assert_debug::<Never>();
+}
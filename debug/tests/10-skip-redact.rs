@@ -0,0 +1,73 @@
+// Fields holding secrets can be kept out of the output:
+//
+//   - #[debug(skip)] leaves the field out entirely. The output then ends with
+//     `..` the way `finish_non_exhaustive` prints it, so it is visible that
+//     something was hidden.
+//   - #[debug(redact)] keeps the field but prints `***` in place of its value.
+//   - #[debug(redact = "len")] prints only the value's length, from its
+//     `len()` method.
+//
+// Neither needs the field's type to implement Debug.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub struct Secret;
+
+#[derive(CustomDebug)]
+pub struct Login<T> {
+    user: &'static str,
+    #[debug(redact)]
+    password: String,
+    #[debug(redact = "len")]
+    token: Vec<u8>,
+    #[debug(skip)]
+    session: T,
+}
+
+#[derive(CustomDebug)]
+pub struct Credentials {
+    user: &'static str,
+    #[debug(redact)]
+    password: String,
+}
+
+#[derive(CustomDebug)]
+pub enum Auth {
+    Key(#[debug(skip)] Secret, u32),
+    Token {
+        #[debug(redact = "len")]
+        token: String,
+    },
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    let login = Login {
+        user: "root",
+        password: "hunter2".to_owned(),
+        token: vec![0; 12],
+        session: Secret,
+    };
+    assert_eq!(
+        format!("{:?}", login),
+        r#"Login { user: "root", password: ***, token: <redacted: 12>, .. }"#
+    );
+    assert_debug::<Login<Secret>>();
+
+    let credentials = Credentials {
+        user: "root",
+        password: "hunter2".to_owned(),
+    };
+    assert_eq!(
+        format!("{:?}", credentials),
+        r#"Credentials { user: "root", password: *** }"#
+    );
+
+    assert_eq!(format!("{:?}", Auth::Key(Secret, 7)), "Key(7, ..)");
+    let token = Auth::Token {
+        token: "abcd".to_owned(),
+    };
+    assert_eq!(format!("{:?}", token), "Token { token: <redacted: 4> }");
+}
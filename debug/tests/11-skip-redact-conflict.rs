@@ -0,0 +1,19 @@
+// A field is shown in one way only, so `skip`, `redact` and a format string
+// cannot be combined. Unknown keys are reported with the ones that exist.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Login {
+    #[debug(skip, redact)]
+    password: String,
+    #[debug = "{:x}"]
+    #[debug(redact = "len")]
+    token: Vec<u8>,
+    #[debug(redact = "length")]
+    key: Vec<u8>,
+    #[debug(hide)]
+    session: u64,
+}
+
+fn main() {}
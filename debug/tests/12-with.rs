@@ -0,0 +1,71 @@
+// #[debug(with = "path::to::fn")] prints a field by calling a function
+//
+//     fn(&T, &mut fmt::Formatter) -> fmt::Result
+//
+// in place of the field's Debug impl. This covers types printed better some
+// other way, raw bytes shown as hex, and foreign types with no Debug at all.
+// The argument may deref-coerce, so a Vec<u8> field can use a function taking
+// &[u8].
+//
+// Since the function does the printing, the field adds no `T: Debug` bound to
+// the impl.
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Debug};
+use std::time::Duration;
+
+mod fmt_helpers {
+    use std::fmt;
+
+    pub fn hex(bytes: &[u8], formatter: &mut fmt::Formatter) -> fmt::Result {
+        for byte in bytes {
+            write!(formatter, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+fn millis(duration: &Duration, formatter: &mut fmt::Formatter) -> fmt::Result {
+    write!(formatter, "{}ms", duration.as_millis())
+}
+
+fn type_name<T>(_: &T, formatter: &mut fmt::Formatter) -> fmt::Result {
+    formatter.write_str(std::any::type_name::<T>())
+}
+
+pub struct Socket;
+
+#[derive(CustomDebug)]
+pub struct Request<T> {
+    #[debug(with = "millis")]
+    timeout: Duration,
+    #[debug(with = "fmt_helpers::hex")]
+    body: Vec<u8>,
+    #[debug(with = "type_name")]
+    transport: T,
+}
+
+#[derive(CustomDebug)]
+pub enum Frame {
+    Data(#[debug(with = "fmt_helpers::hex")] [u8; 4]),
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    let request = Request {
+        timeout: Duration::from_millis(1500),
+        body: vec![0xde, 0xad, 0xbe, 0xef],
+        transport: Socket,
+    };
+    assert_eq!(
+        format!("{:?}", request),
+        format!(
+            "Request {{ timeout: 1500ms, body: deadbeef, transport: {}::Socket }}",
+            module_path!(),
+        )
+    );
+    assert_debug::<Request<Socket>>();
+
+    assert_eq!(format!("{:?}", Frame::Data([1, 2, 3, 4])), "Data(01020304)");
+}
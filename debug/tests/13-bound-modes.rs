@@ -0,0 +1,110 @@
+// The inferred bounds can be adjusted without writing all of them by hand.
+//
+// On a field, #[debug(bound = "...")] replaces only the bounds that field's
+// type would have added; the other fields are still inferred from as usual.
+//
+// On the type, #[debug(bound(infer = "..."))] picks how bounds are inferred:
+//
+//   - "none" adds no bounds beyond those on the type and its fields, for types
+//     whose declared bounds already cover everything printed;
+//   - "params" adds `T: Debug` for every type parameter, whether or not a
+//     printed field uses it;
+//   - "fields" adds `Type: Debug` for the type of every printed field. This
+//     handles wrappers whose Debug impl needs more than `T: Debug`, but
+//     overflows on recursive types like One<T> and Two<T> from test 06, which
+//     is why it is not the default.
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Debug};
+use std::marker::PhantomData;
+
+pub trait Trait {
+    type Value;
+}
+
+pub struct Field<T: Trait> {
+    values: Vec<T::Value>,
+}
+
+impl<T: Trait> Debug for Field<T>
+where
+    T::Value: Debug,
+{
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.debug_list().entries(&self.values).finish()
+    }
+}
+
+#[derive(CustomDebug)]
+pub struct Wrapper<T: Trait, U> {
+    #[debug(bound = "T::Value: Debug")]
+    field: Field<T>,
+    normal: U,
+}
+
+#[derive(CustomDebug)]
+#[debug(bound(infer = "none"))]
+pub struct Setting<T: Debug + Default> {
+    value: T,
+}
+
+#[derive(CustomDebug)]
+#[debug(bound(infer = "params"))]
+pub struct Tagged<T> {
+    marker: PhantomData<T>,
+    tag: &'static str,
+}
+
+#[derive(CustomDebug)]
+#[debug(bound(infer = "fields"))]
+pub struct Holder<T: Trait> {
+    field: Field<T>,
+    count: usize,
+}
+
+struct Id;
+
+impl Trait for Id {
+    type Value = u8;
+}
+
+struct NotDebug;
+
+impl Trait for NotDebug {
+    type Value = u8;
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    // Neither `T: Debug` nor `U: Debug` is inferred from `field`.
+    assert_debug::<Wrapper<NotDebug, u8>>();
+    let wrapper = Wrapper::<Id, _> {
+        field: Field { values: vec![1, 2] },
+        normal: "x",
+    };
+    assert_eq!(
+        format!("{:?}", wrapper),
+        r#"Wrapper { field: [1, 2], normal: "x" }"#,
+    );
+
+    let setting = Setting { value: 3 };
+    assert_eq!(format!("{:?}", setting), "Setting { value: 3 }");
+
+    assert_debug::<Tagged<u8>>();
+    let tagged = Tagged::<u8> {
+        marker: PhantomData,
+        tag: "t",
+    };
+    assert_eq!(
+        format!("{:?}", tagged),
+        r#"Tagged { marker: PhantomData<u8>, tag: "t" }"#,
+    );
+
+    assert_debug::<Holder<NotDebug>>();
+    let holder = Holder::<Id> {
+        field: Field { values: vec![7] },
+        count: 1,
+    };
+    assert_eq!(format!("{:?}", holder), "Holder { field: [7], count: 1 }");
+}
@@ -0,0 +1,101 @@
+// #[derive(CustomDisplay)] implements Display from a template given with
+// #[display("...")] on a struct or on each variant of an enum.
+//
+// The template is a format string whose placeholders refer to fields, by name
+// as in `{name}` or by position as in `{0}`, and may carry the usual format
+// specs, so `{bitmask:#010b}` prints a field in binary. Widths and precisions
+// can come from fields too, as in `{value:.digits$}`.
+//
+// Bounds are inferred the way CustomDebug infers them, but only from the
+// fields the template uses, each with the trait its placeholder needs:
+// `{bits:b}` on a field of type `T` adds `T: Binary`.
+//
+// Resources:
+//
+//   - Syntax of format strings:
+//     https://doc.rust-lang.org/std/fmt/index.html#syntax
+
+use derive_debug::CustomDisplay;
+use std::fmt::{Binary, Display};
+
+#[derive(CustomDisplay)]
+#[display("{name} ({bitmask:#010b})")]
+pub struct Field {
+    name: &'static str,
+    bitmask: u8,
+}
+
+#[derive(CustomDisplay)]
+#[display("{0}.{1}")]
+pub struct Version(u32, u32);
+
+#[derive(CustomDisplay)]
+pub enum Shape {
+    #[display("circle of radius {radius}")]
+    Circle { radius: f64 },
+    #[display("{}x{}")]
+    Rect(u32, u32),
+    #[display("point")]
+    Point,
+}
+
+#[derive(CustomDisplay)]
+#[display("{value:.digits$} ({value:e}, {{escaped}})")]
+pub struct Measurement {
+    value: f64,
+    digits: usize,
+}
+
+#[derive(CustomDisplay)]
+#[display("[{0:01$}]")]
+pub struct Padded(u32, usize);
+
+#[derive(CustomDisplay)]
+#[display("{bits:b}")]
+pub struct Flags<T> {
+    bits: T,
+    pub label: NotDisplay,
+}
+
+pub struct NotDisplay;
+
+fn assert_display<F: Display>() {}
+
+fn assert_binary_flags<T: Binary>() {
+    assert_display::<Flags<T>>();
+}
+
+fn main() {
+    let field = Field {
+        name: "F",
+        bitmask: 0b00011100,
+    };
+    assert_eq!(field.to_string(), "F (0b00011100)");
+
+    assert_eq!(Version(1, 2).to_string(), "1.2");
+
+    assert_eq!(
+        Shape::Circle { radius: 1.5 }.to_string(),
+        "circle of radius 1.5",
+    );
+    assert_eq!(Shape::Rect(3, 4).to_string(), "3x4");
+    assert_eq!(Shape::Point.to_string(), "point");
+
+    let measurement = Measurement {
+        value: 1234.5678,
+        digits: 2,
+    };
+    assert_eq!(
+        measurement.to_string(),
+        "1234.57 (1.2345678e3, {escaped})",
+    );
+
+    assert_eq!(Padded(7, 4).to_string(), "[0007]");
+
+    assert_binary_flags::<u8>();
+    let flags = Flags {
+        bits: 5u8,
+        label: NotDisplay,
+    };
+    assert_eq!(flags.to_string(), "101");
+}
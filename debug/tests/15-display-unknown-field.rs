@@ -0,0 +1,20 @@
+// A template naming a field that does not exist fails to compile, with the
+// error pointing at the template.
+
+use derive_debug::CustomDisplay;
+
+#[derive(CustomDisplay)]
+#[display("{name} ({mask:#010b})")]
+pub struct Field {
+    name: &'static str,
+    bitmask: u8,
+}
+
+#[derive(CustomDisplay)]
+pub enum Token {
+    #[display("{0}+{1}")]
+    Plus(u32),
+    Minus(u32),
+}
+
+fn main() {}
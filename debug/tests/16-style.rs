@@ -0,0 +1,90 @@
+// #[debug(style = "...")] changes the shape of the output.
+//
+// "compact" writes the value on one line, ignoring the `{:#?}` flag, so that
+// the output stays on one line even when a caller higher up pretty-prints the
+// value containing it. Only the derive's own separators lose their spaces;
+// each field is printed with a plain `{:?}` and keeps whatever spaces its own
+// Debug impl writes:
+//
+//     Field{name:"F",bitmask:0b00011100,tags:["a", "b"]}
+//
+// "tuple" leaves out field names, printing a named struct the way a tuple
+// struct is printed. It still follows `{:#?}`.
+//
+// Both styles keep the per-field #[debug = "..."] formats and the other field
+// attributes.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(style = "compact")]
+pub struct Field {
+    name: &'static str,
+    #[debug = "0b{:08b}"]
+    bitmask: u8,
+    tags: Vec<&'static str>,
+}
+
+#[derive(CustomDebug)]
+#[debug(style = "compact")]
+pub enum Event {
+    Key { code: u32, #[debug(skip)] repeat: bool },
+    Click(i32, i32),
+    Quit,
+}
+
+#[derive(CustomDebug)]
+#[debug(style = "tuple")]
+pub struct Point {
+    x: i32,
+    #[debug = "{:+}"]
+    y: i32,
+}
+
+#[derive(Debug)]
+pub struct Log {
+    pub field: Field,
+    pub point: Point,
+}
+
+fn main() {
+    let field = Field {
+        name: "F",
+        bitmask: 0b00011100,
+        tags: vec!["a", "b"],
+    };
+    assert_eq!(
+        format!("{:?}", field),
+        r#"Field{name:"F",bitmask:0b00011100,tags:["a", "b"]}"#,
+    );
+    assert_eq!(
+        format!("{:#?}", field),
+        r#"Field{name:"F",bitmask:0b00011100,tags:["a", "b"]}"#,
+    );
+
+    let key = Event::Key {
+        code: 13,
+        repeat: false,
+    };
+    assert_eq!(format!("{:?}", key), "Key{code:13,..}");
+    assert_eq!(format!("{:?}", Event::Click(1, -2)), "Click(1,-2)");
+    assert_eq!(format!("{:?}", Event::Quit), "Quit");
+
+    let point = Point { x: 1, y: 2 };
+    assert_eq!(format!("{:?}", point), "Point(1, +2)");
+    assert_eq!(format!("{:#?}", point), "Point(\n    1,\n    +2,\n)");
+
+    let log = Log { field, point };
+    assert_eq!(
+        format!("{:#?}", log),
+        concat!(
+            "Log {\n",
+            "    field: Field{name:\"F\",bitmask:0b00011100,tags:[\"a\", \"b\"]},\n",
+            "    point: Point(\n",
+            "        1,\n",
+            "        +2,\n",
+            "    ),\n",
+            "}",
+        ),
+    );
+}
@@ -0,0 +1,55 @@
+// Wire-format structs often have Rust names that differ from the names used
+// by the protocol, which are the ones operators look for in logs.
+//
+// #[debug(rename = "...")] on a field and #[debug(name = "...")] on a struct
+// or an enum variant print those names in place of the Rust ones. Without
+// them the output stays as in test 02.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(name = "FIELD")]
+pub struct Field {
+    name: &'static str,
+    #[debug(rename = "bitMask")]
+    #[debug = "0b{:08b}"]
+    bitmask: u8,
+}
+
+#[derive(CustomDebug)]
+pub enum Frame {
+    #[debug(name = "HEADERS")]
+    Headers {
+        #[debug(rename = "streamId")]
+        stream_id: u32,
+    },
+    #[debug(name = "PING")]
+    Ping(u64),
+}
+
+#[derive(CustomDebug)]
+#[debug(name = "SETTINGS", style = "compact")]
+pub struct Settings {
+    #[debug(rename = "maxFrameSize")]
+    max_frame_size: u32,
+}
+
+fn main() {
+    let field = Field {
+        name: "F",
+        bitmask: 0b00011100,
+    };
+    assert_eq!(
+        format!("{:?}", field),
+        r#"FIELD { name: "F", bitMask: 0b00011100 }"#,
+    );
+
+    let headers = Frame::Headers { stream_id: 3 };
+    assert_eq!(format!("{:?}", headers), "HEADERS { streamId: 3 }");
+    assert_eq!(format!("{:?}", Frame::Ping(7)), "PING(7)");
+
+    let settings = Settings {
+        max_frame_size: 16384,
+    };
+    assert_eq!(format!("{:?}", settings), "SETTINGS{maxFrameSize:16384}");
+}
@@ -0,0 +1,62 @@
+// #[debug(transparent)] prints a newtype as its inner value, without the
+// wrapper's name around it:
+//
+//     UserId(42)  =>  42
+//
+// The struct must have exactly one field besides PhantomData markers, which
+// are left out as in test 05. The formatter is handed to the field with all
+// of its flags, so `{:#?}` and `{:x?}` work as they would on the field
+// itself, and the field's own #[debug = "..."] or `redact` still apply.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct UserId(u64);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Id<T> {
+    raw: u32,
+    marker: PhantomData<T>,
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Tags(Vec<&'static str>);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Token(#[debug(redact)] pub String);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Mask {
+    #[debug = "0b{:08b}"]
+    bits: u8,
+}
+
+pub struct NotDebug;
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_eq!(format!("{:?}", UserId(42)), "42");
+    assert_eq!(format!("{:x?}", UserId(255)), "ff");
+
+    assert_debug::<Id<NotDebug>>();
+    let id = Id::<NotDebug> {
+        raw: 7,
+        marker: PhantomData,
+    };
+    assert_eq!(format!("{:?}", id), "7");
+
+    let tags = Tags(vec!["a", "b"]);
+    assert_eq!(format!("{:?}", tags), r#"["a", "b"]"#);
+    assert_eq!(format!("{:#?}", tags), "[\n    \"a\",\n    \"b\",\n]");
+
+    assert_eq!(format!("{:?}", Token("secret".to_owned())), "***");
+    assert_eq!(format!("{:?}", Mask { bits: 5 }), "0b00000101");
+}
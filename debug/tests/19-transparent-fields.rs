@@ -0,0 +1,26 @@
+// A transparent struct prints its one field, so it must have exactly one
+// besides PhantomData markers, and that field cannot be skipped.
+
+use derive_debug::CustomDebug;
+use std::marker::PhantomData;
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Pair(u8, u8);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Marker<T>(PhantomData<T>);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Hidden(#[debug(skip)] u8);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub enum Either {
+    Left(u8),
+    Right(u8),
+}
+
+fn main() {}
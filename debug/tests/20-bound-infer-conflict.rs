@@ -0,0 +1,13 @@
+// `bound = "..."` replaces every inferred bound, so combining it with
+// `bound(infer = "...")` is reported on the `infer` key.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(bound = "T: std::fmt::Debug")]
+#[debug(bound(infer = "fields"))]
+pub struct Wrapper<T> {
+    value: T,
+}
+
+fn main() {}
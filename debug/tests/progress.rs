@@ -1,12 +1,24 @@
 #[test]
 fn tests() {
     let t = trybuild::TestCases::new();
-    //t.pass("tests/01-parse.rs");
-    //t.pass("tests/02-impl-debug.rs");
-    //t.pass("tests/03-custom-format.rs");
-    //t.pass("tests/04-type-parameter.rs");
-    //t.pass("tests/05-phantom-data.rs");
-    //t.pass("tests/06-bound-trouble.rs");
-    //t.pass("tests/07-associated-type.rs");
-    //t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/01-parse.rs");
+    t.pass("tests/02-impl-debug.rs");
+    t.pass("tests/03-custom-format.rs");
+    t.pass("tests/04-type-parameter.rs");
+    t.pass("tests/05-phantom-data.rs");
+    t.pass("tests/06-bound-trouble.rs");
+    t.pass("tests/07-associated-type.rs");
+    t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/09-enum.rs");
+    t.pass("tests/10-skip-redact.rs");
+    t.compile_fail("tests/11-skip-redact-conflict.rs");
+    t.pass("tests/12-with.rs");
+    t.pass("tests/13-bound-modes.rs");
+    t.pass("tests/14-display.rs");
+    t.compile_fail("tests/15-display-unknown-field.rs");
+    t.pass("tests/16-style.rs");
+    t.pass("tests/17-rename.rs");
+    t.pass("tests/18-transparent.rs");
+    t.compile_fail("tests/19-transparent-fields.rs");
+    t.compile_fail("tests/20-bound-infer-conflict.rs");
 }